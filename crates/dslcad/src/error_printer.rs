@@ -1,8 +1,10 @@
-use crate::parser::{ParseError, Reader};
-use crate::runtime::{RuntimeError, WithStack};
+use crate::parser::{ParseError, ParseErrors, Reader};
+use crate::runtime::{RuntimeError, StackFrame, WithStack};
 use crate::source::LineColExt;
+use serde_json::{json, Value};
 use std::error::Error;
 use std::io::{Result, Write};
+use std::ops::Range;
 
 pub struct ErrorPrinter<R: Reader> {
     reader: R,
@@ -13,38 +15,194 @@ impl<R: Reader> ErrorPrinter<R> {
         Self { reader }
     }
 
+    pub fn print_parse_errors(&self, mut w: impl Write, parse_errors: &ParseErrors) -> Result<()> {
+        for (i, parse_error) in parse_errors.errors.iter().enumerate() {
+            if i != 0 {
+                writeln!(w)?;
+            }
+            self.print_parse_error(&mut w, parse_error)?;
+        }
+        Ok(())
+    }
+
     pub fn print_parse_error(&self, mut w: impl Write, parse_error: &ParseError) -> Result<()> {
         let source = self.reader.read(parse_error.file.to_path());
         write!(w, "error in {}", parse_error.file.to_str())?;
-        if let Ok(source) = source {
+        let location = source.ok().map(|source| {
             let (line, col) = parse_error.error.line_col(&source);
+            (source, line, col)
+        });
+        if let Some((_, line, col)) = &location {
             write!(w, "[{}:{}-{}]", line, col.start, col.end)?;
         }
         writeln!(w, ":")?;
 
         writeln!(w, "{}", parse_error.error)?;
+        if let Some((source, line, col)) = &location {
+            Self::print_excerpt(&mut w, source, *line, col, "")?;
+        }
+        Ok(())
+    }
+
+    /// Print each parse error as a JSON object on its own line.
+    pub fn print_parse_errors_json(
+        &self,
+        mut w: impl Write,
+        parse_errors: &ParseErrors,
+    ) -> Result<()> {
+        for parse_error in &parse_errors.errors {
+            let mut diagnostic = json!({
+                "kind": "parse",
+                "code": parse_error.error.code(),
+                "message": parse_error.error.to_string(),
+                "file": parse_error.file.to_path().to_string_lossy(),
+                "span": null,
+                "line": null,
+                "column": null,
+            });
+            if let Some(span) = parse_error.error.span() {
+                diagnostic["span"] = json!({ "start": span.start, "end": span.end });
+            }
+            if let Ok(source) = self.reader.read(parse_error.file.to_path()) {
+                let (line, col) = parse_error.error.line_col(&source);
+                diagnostic["line"] = json!(line);
+                diagnostic["column"] = json!({ "start": col.start, "end": col.end });
+            }
+            writeln!(w, "{diagnostic}")?;
+        }
         Ok(())
     }
 
+    /// Print a runtime error and its stack, innermost frame first, as a JSON object.
+    pub fn print_runtime_error_json(
+        &self,
+        mut w: impl Write,
+        runtime_error: &WithStack<RuntimeError>,
+    ) -> Result<()> {
+        let stack: Vec<_> = runtime_error
+            .stack
+            .iter()
+            .rev()
+            .map(|frame| self.frame_json(frame))
+            .collect();
+
+        let mut diagnostic = json!({
+            "kind": "runtime",
+            "code": runtime_error.error.code(),
+            "message": runtime_error.error.to_string(),
+            "file": null,
+            "span": null,
+            "line": null,
+            "column": null,
+        });
+        if let Some(Value::Object(location)) = stack.first() {
+            for (key, value) in location {
+                diagnostic[key] = value.clone();
+            }
+        }
+        diagnostic["stack"] = json!(stack);
+
+        writeln!(w, "{diagnostic}")
+    }
+
+    /// Print an error without a location, with the same fields as parse and runtime errors.
+    pub fn print_error_json(
+        &self,
+        mut w: impl Write,
+        code: &str,
+        error: &impl Error,
+    ) -> Result<()> {
+        let diagnostic = json!({
+            "kind": "error",
+            "code": code,
+            "message": error.to_string(),
+            "file": null,
+            "span": null,
+            "line": null,
+            "column": null,
+        });
+        writeln!(w, "{diagnostic}")
+    }
+
+    fn frame_json(&self, frame: &StackFrame) -> Value {
+        let path = frame.document.to_path();
+        let mut location = json!({
+            "file": path.to_string_lossy(),
+            "span": { "start": frame.span.start, "end": frame.span.end },
+            "line": null,
+            "column": null,
+        });
+        if let Ok(source) = self.reader.read(path) {
+            let (line, col) = frame.span.line_col(&source);
+            location["line"] = json!(line);
+            location["column"] = json!({ "start": col.start, "end": col.end });
+        }
+        location
+    }
+
+    /// Print the source line `line` with a caret under the columns in `col`, followed by `label`.
+    fn print_excerpt(
+        mut w: impl Write,
+        source: &str,
+        line: usize,
+        col: &Range<usize>,
+        label: &str,
+    ) -> Result<()> {
+        let Some(text) = line.checked_sub(1).and_then(|i| source.split('\n').nth(i)) else {
+            return Ok(());
+        };
+        let text = text.trim_end();
+        let start = col.start.min(text.len());
+        let end = col.end.clamp(start, text.len());
+
+        // columns are bytes, the caret moves one character at a time and keeps tabs so it lines
+        // up however wide they are drawn
+        let padding: String = text
+            .get(..start)
+            .unwrap_or_default()
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = text.get(start..end).map_or(0, |s| s.chars().count()).max(1);
+
+        let gutter = line.to_string();
+        writeln!(w, "{gutter} | {text}")?;
+        write!(
+            w,
+            "{} | {}{}",
+            " ".repeat(gutter.len()),
+            padding,
+            "^".repeat(width)
+        )?;
+        if !label.is_empty() {
+            write!(w, " {label}")?;
+        }
+        writeln!(w)
+    }
+
     pub fn print_runtime_error(
         &self,
         mut w: impl Write,
         runtime_error: &WithStack<RuntimeError>,
     ) -> Result<()> {
-        writeln!(w, "error:")?;
-        writeln!(w, "{}", runtime_error.error)?;
-        writeln!(w)?;
-        writeln!(w, "stacktrace:")?;
+        writeln!(w, "error: {}", runtime_error.error)?;
 
-        for frame in runtime_error.stack.iter().rev() {
-            let source = self.reader.read(frame.document.to_path());
+        for (i, frame) in runtime_error.stack.iter().rev().enumerate() {
+            let label = if i == 0 {
+                "error occurred here"
+            } else {
+                "called from here"
+            };
 
-            write!(w, "{}", frame.document.to_str())?;
-            if let Ok(source) = source {
+            let path = frame.document.to_path();
+            write!(w, " --> {}", path.to_string_lossy())?;
+            if let Ok(source) = self.reader.read(path) {
                 let (line, col) = frame.span.line_col(&source);
-                write!(w, "[{}:{}-{}]", line, col.start, col.end)?;
+                writeln!(w, "[{}:{}-{}]", line, col.start, col.end)?;
+                Self::print_excerpt(&mut w, &source, line, &col, label)?;
+            } else {
+                writeln!(w)?;
             }
-            writeln!(w)?;
         }
         Ok(())
     }
@@ -59,6 +217,7 @@ impl<R: Reader> ErrorPrinter<R> {
 mod tests {
     use super::*;
     use crate::parser::{DocId, DocumentParseError};
+    use crate::runtime::{StackFrame, Type};
     use crate::tests::TestReader;
 
     #[test]
@@ -92,7 +251,132 @@ an error occurred when formatting an argument
         assert_eq!(
             String::from_utf8(buf).unwrap(),
             r"error in test.ds[1:5-10]:
-expected test but found foo
+expected test, found foo
+1 | hello world
+  |      ^^^^^
+"
+        );
+    }
+
+    #[test]
+    fn it_prints_runtime_errors_with_source() {
+        let mut buf = Vec::new();
+        let code = "var a = 5;\ncube(x=a) -> length();";
+        let printer = ErrorPrinter::new(TestReader(code));
+        let error = WithStack {
+            error: RuntimeError::UnexpectedType {
+                expected: Type::List,
+                actual: Type::Shape,
+            },
+            stack: vec![StackFrame {
+                document: DocId::new("test.ds".to_string()),
+                span: 11..33,
+            }],
+        };
+        printer.print_runtime_error(&mut buf, &error).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r"error: mismatched types, expected list but found shape
+ --> test.ds[2:0-22]
+2 | cube(x=a) -> length();
+  | ^^^^^^^^^^^^^^^^^^^^^^ error occurred here
+"
+        );
+    }
+
+    #[test]
+    fn it_keeps_tabs_before_the_caret() {
+        let mut buf = Vec::new();
+        let code = "{\n\t\tlength();\n}";
+        let printer = ErrorPrinter::new(TestReader(code));
+        let error = WithStack {
+            error: RuntimeError::NoReturnValue(),
+            stack: vec![StackFrame {
+                document: DocId::new("test.ds".to_string()),
+                span: 4..12,
+            }],
+        };
+        printer.print_runtime_error(&mut buf, &error).unwrap();
+
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.contains("2 | \t\tlength();\n  | \t\t^^^^^^^^ error occurred here"));
+    }
+
+    #[test]
+    fn it_prints_errors_as_json() {
+        let mut buf = Vec::new();
+        let printer = ErrorPrinter::new(TestReader("var a = 5;\ncube(x=a) -> length();"));
+        let error = WithStack {
+            error: RuntimeError::UnexpectedType {
+                expected: Type::List,
+                actual: Type::Shape,
+            },
+            stack: vec![StackFrame {
+                document: DocId::new("test.ds".to_string()),
+                span: 11..33,
+            }],
+        };
+        printer.print_runtime_error_json(&mut buf, &error).unwrap();
+
+        let diagnostic: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!("runtime", diagnostic["kind"]);
+        assert_eq!("UnexpectedType", diagnostic["code"]);
+        assert_eq!("test.ds", diagnostic["file"]);
+        assert_eq!(2, diagnostic["line"]);
+        assert_eq!(22, diagnostic["column"]["end"]);
+        assert_eq!(1, diagnostic["stack"].as_array().unwrap().len());
+
+        let mut buf = Vec::new();
+        let errors = crate::parser::Parser::new(
+            TestReader("var = 1;\nvar = 2;"),
+            DocId::new("test.ds".to_string()),
+        )
+        .parse()
+        .unwrap_err();
+        printer.print_parse_errors_json(&mut buf, &errors).unwrap();
+
+        let lines: Vec<Value> = String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(2, lines.len());
+        assert_eq!("ExpectedOneOf", lines[1]["code"]);
+        assert_eq!(13, lines[1]["span"]["start"]);
+
+        let mut buf = Vec::new();
+        printer
+            .print_error_json(&mut buf, "Io", &std::fmt::Error)
+            .unwrap();
+        let diagnostic: Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!("Io", diagnostic["code"]);
+        assert!(diagnostic["file"].is_null());
+    }
+
+    #[test]
+    fn it_prints_every_parse_error() {
+        let mut buf = Vec::new();
+        let printer = ErrorPrinter::new(TestReader("var = 1;\nvar = 2;"));
+        let errors = crate::parser::Parser::new(
+            TestReader("var = 1;\nvar = 2;"),
+            DocId::new("test.ds".to_string()),
+        )
+        .parse()
+        .unwrap_err();
+        printer.print_parse_errors(&mut buf, &errors).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            r"error in test.ds[1:4-5]:
+expected identifier, found =
+1 | var = 1;
+  |     ^
+
+error in test.ds[2:4-5]:
+expected identifier, found =
+2 | var = 2;
+  |     ^
 "
         );
     }
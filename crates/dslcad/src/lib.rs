@@ -1,26 +1,53 @@
 use crate::library::Library;
-use crate::parser::{Ast, DocId, DocumentParseError, Literal, ParseError, Parser};
+use crate::parser::{Ast, DocId, DocumentParseError, Expression, ParseErrors, Parser};
+use crate::profiler::Profiler;
 use crate::reader::FsReader;
 use crate::resources::ResourceExt;
-use crate::runtime::{Engine, RuntimeError, Value, WithStack};
+use crate::runtime::{Cache, Engine, RuntimeError, Value, WithStack};
+use crate::sweep::Sweep;
 use dslcad_storage::protocol::{Part, Render};
+use dslcad_storage::threemf::Unit;
 use log::trace;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::Instant;
 
 pub mod error_printer;
 pub mod library;
+pub mod parameters;
+pub mod params_file;
 pub mod parser;
+pub mod profiler;
+pub mod project;
 pub mod reader;
 mod resources;
 pub mod runtime;
 mod source;
+mod suggestion;
+pub mod sweep;
+pub mod testing;
 mod trace;
 
-pub fn parse(source: String) -> Result<Ast, ParseError> {
+/// Settings that change how documents are parsed.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Unit that numbers like `2in` are converted to.
+    pub unit: Unit,
+    /// Directories searched, in order, for paths such as `<std>/fasteners/m3`.
+    pub library_paths: Vec<PathBuf>,
+}
+
+pub fn parse(source: String) -> Result<Ast, ParseErrors> {
+    parse_with_options(source, &ParseOptions::default())
+}
+
+pub fn parse_with_options(source: String, options: &ParseOptions) -> Result<Ast, ParseErrors> {
     let parse_time = Instant::now();
 
-    let parser = Parser::new(FsReader, DocId::new(source)).with_default_loaders();
+    let parser = Parser::new(FsReader, DocId::new(source))
+        .with_unit(options.unit)
+        .with_library_paths(options.library_paths.clone())
+        .with_default_loaders();
     let ast = parser.parse();
 
     trace!("parse in {}s", parse_time.elapsed().as_secs_f64());
@@ -29,13 +56,21 @@ pub fn parse(source: String) -> Result<Ast, ParseError> {
 }
 
 /// Parse arguments for use in DSLCAD.
-/// Arguments take the form of name=literal
+/// Arguments take the form of name=expression, where the expression can only use builtins
 pub fn parse_arguments<'a>(
     arguments: impl Iterator<Item = &'a str>,
-) -> Result<HashMap<&'a str, Literal>, DocumentParseError> {
+) -> Result<HashMap<&'a str, Expression>, DocumentParseError> {
+    parse_arguments_with_unit(arguments, Unit::default())
+}
+
+/// Parse arguments with numbers like `2in` converted to `unit` rather than millimeters.
+pub fn parse_arguments_with_unit<'a>(
+    arguments: impl Iterator<Item = &'a str>,
+    unit: Unit,
+) -> Result<HashMap<&'a str, Expression>, DocumentParseError> {
     let parse_time = Instant::now();
 
-    let parser = Parser::new((), DocId::new(String::new()));
+    let parser = Parser::new((), DocId::new(String::new())).with_unit(unit);
     let arguments = parser.parse_arguments(arguments)?;
 
     trace!("arguments in {}s", parse_time.elapsed().as_secs_f64());
@@ -43,9 +78,18 @@ pub fn parse_arguments<'a>(
     Ok(arguments)
 }
 
+/// Parse arguments that may list several values, such as `size=10|20` or `wall=1..3 step 0.5`.
+pub fn parse_sweep<'a>(
+    arguments: impl Iterator<Item = &'a str>,
+    unit: Unit,
+) -> Result<Sweep<'a>, DocumentParseError> {
+    let parser = Parser::new((), DocId::new(String::new())).with_unit(unit);
+    Ok(Sweep::new(parser.parse_sweep(arguments)?))
+}
+
 pub fn eval(
     documents: Ast,
-    arguments: HashMap<&str, Literal>,
+    arguments: HashMap<&str, Expression>,
 ) -> Result<Value, WithStack<RuntimeError>> {
     let lib = Library::default();
 
@@ -58,6 +102,43 @@ pub fn eval(
     Ok(instance)
 }
 
+/// Evaluate like [eval] while reusing and storing builtin results in `cache`.
+pub fn eval_with_cache(
+    documents: &Ast,
+    arguments: HashMap<&str, Expression>,
+    cache: &Cache,
+) -> Result<Value, WithStack<RuntimeError>> {
+    let lib = Library::default();
+
+    let mut engine = Engine::new(&lib, documents).with_cache(cache);
+
+    let eval_time = Instant::now();
+    let instance = engine.eval_root(arguments)?;
+    trace!("eval in {}s", eval_time.elapsed().as_secs_f64());
+
+    Ok(instance)
+}
+
+/// Evaluate like [eval_with_cache] while recording timings in `profiler`.
+pub fn eval_with_profiler(
+    documents: &Ast,
+    arguments: HashMap<&str, Expression>,
+    cache: &Cache,
+    profiler: &Profiler,
+) -> Result<Value, WithStack<RuntimeError>> {
+    let lib = Library::default();
+
+    let mut engine = Engine::new(&lib, documents)
+        .with_cache(cache)
+        .with_profiler(profiler);
+
+    let eval_time = Instant::now();
+    let instance = engine.eval_root(arguments)?;
+    trace!("eval in {}s", eval_time.elapsed().as_secs_f64());
+
+    Ok(instance)
+}
+
 pub fn render(instance: Value, deflection: f64) -> Result<Render, RuntimeError> {
     let render_time = Instant::now();
 
@@ -96,11 +177,12 @@ mod tests {
     use super::*;
     use crate::library::Library;
     use crate::parser::{Ast, DocId, Reader};
-    use crate::runtime::{Engine, Value};
+    use crate::runtime::{Engine, Type, Value};
     use crate::{parse_arguments, render};
     use std::collections::HashMap;
     use std::io::Error;
     use std::path::{Path, PathBuf};
+    use std::sync::Arc;
 
     fn parse_str(code: &'static str) -> Ast {
         let reader = TestReader(code);
@@ -179,6 +261,157 @@ mod tests {
         run("cube() -> scale(z=2);");
     }
 
+    #[test]
+    fn it_has_twisted_extrusions() {
+        run("square() -> extrude(z=10, twist=90);");
+        run("square() -> extrude(z=10, scale_top=0.5);");
+    }
+
+    #[test]
+    fn it_has_2d_booleans() {
+        run("square(x=10, y=10) ->left difference(right=circle(radius=2)) -> extrude(z=1);");
+        run("square(x=10, y=10) ->left intersect(right=circle(radius=8));");
+        run("square(x=10, y=10) ->left merge(right=circle(radius=8)) -> extrude(z=1);");
+        assert_eq!(
+            Type::Edge,
+            run("line(start=point(), end=point(x=1)) ->left union(right=line(start=point(x=1), end=point(y=1)));")
+                .get_type()
+        );
+    }
+
+    #[test]
+    fn it_calls_overloads_through_documents() {
+        run("var profile = func { square(x=10, y=10); }; profile() -> extrude(z=1, twist=10);");
+        run("var profile = func { circle(); }; profile() -> translate(x=1) -> offset(distance=1);");
+    }
+
+    #[test]
+    fn it_has_faces_with_holes() {
+        run("face(outer=square(x=10, y=10), holes=[circle(radius=2)]) -> extrude(z=1);");
+        run("face(outer=square(x=10, y=10), holes=[circle(radius=2)]) -> offset(distance=1);");
+    }
+
+    #[test]
+    fn it_has_offset_joins() {
+        run("square(x=10, y=10) -> offset(distance=1, join=\"intersection\");");
+        run("line(start=point(), end=point(x=10)) -> offset(distance=1);");
+        assert!(try_run("square() -> offset(distance=1, join=\"round\");").is_err());
+    }
+
+    #[test]
+    fn it_can_offset_shapes() {
+        run("cube(x=10, y=10, z=10) -> offset(distance=0.2);");
+        run("cube(x=10, y=10, z=10) -> offset(distance=-1);");
+        assert!(try_run("cube(x=10, y=10, z=10) -> offset(distance=-6);").is_err());
+    }
+
+    #[test]
+    fn it_reuses_cached_geometry() {
+        let cache = Cache::new();
+        let code = "cube(x=10, y=10, z=10) -> fillet(radius=1);";
+
+        let first = eval_with_cache(&parse_str(code), HashMap::new(), &cache).unwrap();
+        let second = eval_with_cache(&parse_str(code), HashMap::new(), &cache).unwrap();
+
+        // cube, fillet and the statement itself
+        assert_eq!(3, cache.len());
+        assert!(Arc::ptr_eq(
+            &first.to_shape().unwrap(),
+            &second.to_shape().unwrap()
+        ));
+    }
+
+    #[test]
+    fn it_only_reevaluates_changed_statements() {
+        let cache = Cache::new();
+        let eval = |code| eval_with_cache(&parse_str(code), HashMap::new(), &cache).unwrap();
+
+        eval("var a = 1; var b = 2; a + b;");
+        assert_eq!(3, cache.len());
+
+        eval("var a = 1; var b = 2; a + b;");
+        assert_eq!(3, cache.len());
+
+        // only b and the statement reading it change
+        assert_eq!(Ok(4.), eval("var a = 1; var b = 3; a + b;").to_number());
+        assert_eq!(5, cache.len());
+    }
+
+    #[test]
+    fn it_reevaluates_documents_that_load_changed_resources() {
+        let cache = Cache::new();
+        let eval = |data| {
+            let files = TestFiles(data);
+            let ast = Parser::new(files, DocId::new("main.ds".to_string()))
+                .with_default_loaders()
+                .parse()
+                .unwrap();
+            eval_with_cache(&ast, HashMap::new(), &cache).unwrap()
+        };
+
+        let first = eval(&[
+            ("main.ds", "./part();"),
+            ("./part.ds", "./data.json();"),
+            ("./data.json", "2"),
+        ]);
+        let second = eval(&[
+            ("main.ds", "./part();"),
+            ("./part.ds", "./data.json();"),
+            ("./data.json", "3"),
+        ]);
+        assert_eq!(Ok(2.), first.to_number());
+        assert_eq!(Ok(3.), second.to_number());
+    }
+
+    #[test]
+    fn it_evaluates_independent_statements_in_order() {
+        let value = run("var a = 1; var b = 2; var c = a + b; var d = c * 2; [a, b, c, d];");
+        let numbers: Vec<_> = value
+            .to_list()
+            .unwrap()
+            .iter()
+            .map(|v| v.to_number().unwrap())
+            .collect();
+        assert_eq!(vec![1., 2., 3., 6.], numbers);
+
+        let value = run("map range(end=100) as i: i * 2;");
+        let numbers: Vec<_> = value
+            .to_list()
+            .unwrap()
+            .iter()
+            .map(|v| v.to_number().unwrap())
+            .collect();
+        assert_eq!((0..100).map(|i| i as f64 * 2.).collect::<Vec<_>>(), numbers);
+    }
+
+    #[test]
+    fn it_checks_assertions() {
+        run("var wall = 2; assert wall >= 2, \"walls are too thin\"; wall;");
+
+        let error =
+            try_run("var wall = 1;\nassert wall >= 2, \"walls are too thin\";").unwrap_err();
+        assert_eq!(
+            RuntimeError::AssertionFailed("walls are too thin".to_string()),
+            error.error
+        );
+        assert_eq!(14..53, error.stack.last().unwrap().span);
+
+        let error = try_run("var wall = 1; assert wall >= 2;").unwrap_err();
+        assert_eq!(
+            RuntimeError::AssertionFailed("wall >= 2".to_string()),
+            error.error
+        );
+    }
+
+    #[test]
+    fn it_measures_shapes() {
+        run("var c = cube(x=10, y=20, z=5);
+            assert approx(left=c.volume, right=1000);
+            assert approx(left=c.size.y, right=20, tolerance=0.01);
+            assert approx(left=c.max.z, right=5, tolerance=0.01);
+            c;");
+    }
+
     #[test]
     fn it_supports_arguments() {
         let args = parse_arguments(vec!["a=\"5\""].into_iter()).unwrap();
@@ -189,6 +422,16 @@ mod tests {
         assert_eq!("5", &res.stdout);
     }
 
+    #[test]
+    fn it_supports_expression_arguments() {
+        let args = parse_arguments(vec!["a=[1, 2, 3]", "b=2 * -3"].into_iter()).unwrap();
+
+        let ast = parse_str("var a; var b; length(list=a) + b;");
+        let res = eval(ast, args).unwrap();
+
+        assert_eq!(Ok(-3.), res.to_number());
+    }
+
     #[test]
     fn it_supports_scopes() {
         assert_eq!(Ok(5.), run("{ 5; };").to_number());
@@ -237,6 +480,87 @@ line(start=point(x=0,y=0), end=point(x=1,y=1))
         assert_eq!(4, parts.len());
     }
 
+    #[test]
+    fn it_imports_public_variables() {
+        let files = TestFiles(&[
+            (
+                "main.ds",
+                "import \"./lib.ds\" as lib; lib.double(x=lib.size);",
+            ),
+            (
+                "./lib.ds",
+                "var size = 2; var _secret = 3; var double = func { var x; x * 2; };",
+            ),
+        ]);
+        let ast = Parser::new(files, DocId::new("main.ds".to_string()))
+            .parse()
+            .unwrap();
+        let value = eval(ast, HashMap::new()).unwrap();
+        assert_eq!(4., value.to_number().unwrap());
+
+        let files = TestFiles(&[
+            ("main.ds", "import \"./lib.ds\" as lib; lib._secret;"),
+            ("./lib.ds", "var _secret = 3;"),
+        ]);
+        let ast = Parser::new(files, DocId::new("main.ds".to_string()))
+            .parse()
+            .unwrap();
+        let error = eval(ast, HashMap::new()).unwrap_err();
+        assert_eq!(
+            RuntimeError::MissingProperty("_secret".to_string()),
+            error.error
+        );
+    }
+
+    #[test]
+    fn it_resolves_library_paths() {
+        let files = TestFiles(&[
+            (
+                "main.ds",
+                "import \"<std>/sizes\" as sizes; <std>/half(x=sizes.big);",
+            ),
+            ("first/std/half.ds", "var x; ./twice(x=x) / 4;"),
+            ("first/std/twice.ds", "var x; x * 2;"),
+            ("second/std/half.ds", "var x; 0;"),
+            ("second/std/sizes.ds", "var big = 8;"),
+        ]);
+        let ast = Parser::new(files, DocId::new("main.ds".to_string()))
+            .with_library_paths(vec!["first".into(), "second".into()])
+            .parse()
+            .unwrap();
+        let value = eval(ast, HashMap::new()).unwrap();
+        assert_eq!(4., value.to_number().unwrap());
+
+        let files = TestFiles(&[("main.ds", "<std>/half(x=1);")]);
+        let errors = Parser::new(files, DocId::new("main.ds".to_string()))
+            .parse()
+            .unwrap_err();
+        assert!(matches!(
+            errors.errors[0].error,
+            DocumentParseError::NotInLibraryPath(_, _)
+        ));
+    }
+
+    /// Reader over a fixed set of files, keyed by path.
+    pub struct TestFiles(pub &'static [(&'static str, &'static str)]);
+    impl Reader for TestFiles {
+        fn read_bytes(&self, path: &Path) -> Result<Vec<u8>, Error> {
+            Ok(self.read(path)?.into_bytes())
+        }
+
+        fn read(&self, path: &Path) -> Result<String, std::io::Error> {
+            self.0
+                .iter()
+                .find(|(name, _)| Path::new(name) == path)
+                .map(|(_, source)| source.to_string())
+                .ok_or_else(|| Error::from(std::io::ErrorKind::NotFound))
+        }
+
+        fn normalize(&self, path: &Path) -> PathBuf {
+            PathBuf::from(path)
+        }
+    }
+
     pub struct TestReader(pub &'static str);
     impl Reader for TestReader {
         fn read_bytes(&self, _: &Path) -> Result<Vec<u8>, Error> {
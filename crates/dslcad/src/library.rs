@@ -7,11 +7,12 @@ mod text;
 mod utils;
 
 use crate::runtime::{RuntimeError, Type, Value};
+use crate::suggestion::did_you_mean;
 use indexmap::IndexMap;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
 
-type Function = dyn Fn(&HashMap<&str, Value>) -> Result<Value, RuntimeError>;
+pub(crate) type Function = dyn Fn(&HashMap<&str, Value>) -> Result<Value, RuntimeError> + Sync;
 
 type Arguments<'a> = Vec<ArgValue<'a>>;
 
@@ -58,17 +59,24 @@ pub struct Signature {
 }
 
 impl Signature {
-    fn call_with<'b>(&self, call: &CallSignature<'b>) -> Option<HashMap<&'b str, Value>> {
+    /// Matches a call against this signature, returning the converted arguments
+    /// along with the number of arguments that needed a type conversion.
+    fn call_with<'b>(&self, call: &CallSignature<'b>) -> Option<(HashMap<&'b str, Value>, usize)> {
         let mut map = HashMap::new();
         let mut to_cover = self.arguments.clone();
+        let mut conversions = 0;
+
+        let mut convert = |value: &Value, access: Access| match access {
+            Access::Required(t) | Access::Optional(t) => {
+                conversions += value.conversion_cost(t);
+                value.to_type(t).ok()
+            }
+            Access::RequiredAny() => Some(value.clone()),
+        };
 
         for (name, value) in call.named() {
             if let Some((name, access)) = to_cover.swap_remove_entry(name) {
-                match access {
-                    Access::Required(t) => map.insert(name, value.to_type(t).ok()?),
-                    Access::Optional(t) => map.insert(name, value.to_type(t).ok()?),
-                    Access::RequiredAny() => map.insert(name, value.clone()),
-                };
+                map.insert(name, convert(value, access)?);
             } else if self.variadic {
                 map.insert(name, value.clone());
             } else {
@@ -78,18 +86,14 @@ impl Signature {
 
         for value in call.unnamed() {
             let (name, access) = to_cover.shift_remove_index(0)?;
-            match access {
-                Access::Required(t) => map.insert(name, value.to_type(t).ok()?),
-                Access::Optional(t) => map.insert(name, value.to_type(t).ok()?),
-                Access::RequiredAny() => map.insert(name, value.clone()),
-            };
+            map.insert(name, convert(value, access)?);
         }
 
         if to_cover.values().any(|a| a.is_required()) {
             return None;
         }
 
-        Some(map)
+        Some((map, conversions))
     }
 }
 
@@ -129,9 +133,11 @@ macro_rules! arguments {
     (bool) => {Access::Required(Type::Bool)};
     (option_bool) => {Access::Optional(Type::Bool)};
     (text) => {Access::Required(Type::Text)};
+    (option_text) => {Access::Optional(Type::Text)};
     (any) => {Access::RequiredAny()};
     (point) => {Access::Required(Type::Point)};
     (edge) => {Access::Required(Type::Edge)};
+    (face) => {Access::Required(Type::Face)};
     (shape) => {Access::Required(Type::Shape)};
     (list) => {Access::Required(Type::List)};
     ($($name: ident=$value: ident), *) => {vec![$((stringify!($name),arguments!($value))), *]};
@@ -177,6 +183,12 @@ macro_rules! invoke {
         value
             .to_text()?
     }};
+    ($map: ident, $name: ident=option_text) => {{
+        match $map.get(stringify!($name)) {
+            Some(value) => Some(value.to_text()?),
+            None => None,
+        }
+    }};
     ($map: ident, $name: ident=point) => {{
         let value = $map
             .get(stringify!($name))
@@ -198,6 +210,13 @@ macro_rules! invoke {
         &value
             .to_line()?
     }};
+    ($map: ident, $name: ident=face) => {{
+        let value = $map
+            .get(stringify!($name))
+            .ok_or(RuntimeError::UnsetParameter(String::from(stringify!($name))))?;
+        &value
+            .to_face()?
+    }};
     ($map: ident, $name: ident=list) => {{
         let value = $map
             .get(stringify!($name))
@@ -246,6 +265,18 @@ impl Library {
         self.lookup.contains_key(name)
     }
 
+    /// Names of every function that is not hidden behind an operator.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.lookup
+            .iter()
+            .filter(|(_, indices)| {
+                indices
+                    .iter()
+                    .any(|i| self.signatures[*i].category != Category::Hidden)
+            })
+            .map(|(name, _)| *name)
+    }
+
     fn from_signatures(signatures: Vec<Signature>) -> Self {
         let lookup = Self::build_lookup(&signatures);
         Library { signatures, lookup }
@@ -259,13 +290,19 @@ impl Library {
             let mut options = Vec::new();
             for i in indices {
                 let signature = &self.signatures[*i];
-                if let Some(full_args) = signature.call_with(&to_call) {
-                    options.push((signature.function, full_args))
+                if let Some((full_args, conversions)) = signature.call_with(&to_call) {
+                    options.push((conversions, signature.function, full_args))
                 }
             }
 
+            // prefer the overloads that need the cheapest type conversions
+            if let Some(fewest) = options.iter().map(|(c, _, _)| *c).min() {
+                options.retain(|(c, _, _)| *c == fewest);
+            }
+
             if options.len() == 1 {
-                return Ok(options.remove(0));
+                let (_, function, full_args) = options.remove(0);
+                return Ok((function, full_args));
             }
 
             Err(RuntimeError::CouldNotFindFunctionSignature {
@@ -278,6 +315,7 @@ impl Library {
         } else {
             Err(RuntimeError::CouldNotFindFunction {
                 name: to_call.name.to_string(),
+                suggestion: did_you_mean(to_call.name, self.names()),
             })
         }
     }
@@ -360,6 +398,12 @@ impl Default for Library {
             bind!(not_equals, math::not_equals[left=number, right=number], Category::Hidden, "not equal"),
             bind!(greater, math::greater[left=number, right=number], Category::Hidden, "greater than"),
             bind!(greater_or_equal, math::greater_or_equal[left=number, right=number], Category::Hidden, "greater than or equal"),
+            bind!(
+                approx,
+                math::approx[left=number, right=number, tolerance=option_number],
+                Category::Math,
+                "test whether two numbers are within tolerance (default 0.001) of each other"
+            ),
             bind!(
                 round,
                 math::round[number = number],
@@ -435,12 +479,17 @@ impl Default for Library {
             ),
             bind!(arc, faces::arc[start=point, center=point, end=point], Category::TwoD, "create an arcing line between three points"),
             bind!(union, faces::union_edge[left=edge, right=edge], Category::TwoD, "combine two edges"),
+            bind!(union, faces::union_face[left=face, right=face], Category::TwoD, "combine two faces"),
+            bind!(merge, faces::union_face[left=face, right=face], Category::TwoD, "merge two closed edges or faces into a single face"),
+            bind!(difference, faces::difference[left=face, right=face], Category::TwoD, "cut one face out of another"),
+            bind!(intersect, faces::intersect[left=face, right=face], Category::TwoD, "intersection between two faces"),
             bind!(
                 face,
                 faces::face[parts = list],
                 Category::TwoD,
                 "make a closed face from a list of points, lines and arcs"
             ),
+            bind!(face, faces::face_with_holes[outer=edge, holes=list], Category::TwoD, "make a face from an outer edge with a list of edges cut out as holes"),
             bind!(translate, faces::translate[shape=edge, x=option_number, y=option_number, z=option_number], Category::TwoD, "move an edge"),
             bind!(rotate, faces::rotate[shape=edge, angle=option_number], Category::TwoD, "rotate an edge"),
             bind!(rotate, faces::rotate_3d[shape=edge, x=option_number, y=option_number, z=option_number], Category::TwoD, "rotate an edge"),
@@ -451,10 +500,22 @@ impl Default for Library {
                 Category::TwoD,
                 "center an edge"
             ),
-            bind!(offset, faces::offset[shape=edge, distance=number], Category::TwoD, "offset an edge"),
+            bind!(offset, faces::offset[shape=edge, distance=number, join=option_text], Category::TwoD, "offset an edge with arc, tangent or intersection joins, open edges are thickened into an outline"),
+            bind!(translate, faces::translate_face[shape=face, x=option_number, y=option_number, z=option_number], Category::TwoD, "move a face"),
+            bind!(rotate, faces::rotate_face[shape=face, angle=option_number], Category::TwoD, "rotate a face"),
+            bind!(scale, faces::scale_face[shape=face, scale=number], Category::TwoD, "scale a face"),
+            bind!(
+                center,
+                faces::center_face[shape = face, x=option_bool, y=option_bool, z=option_bool],
+                Category::TwoD,
+                "center a face"
+            ),
+            bind!(offset, faces::offset_face[shape=face, distance=number, join=option_text], Category::TwoD, "offset a face, holes shrink as the outline grows"),
             // 3D
-            bind!(extrude, faces::extrude[shape=edge, x=option_number, y=option_number, z=option_number], Category::ThreeD, "extrude a face into a 3D shape"),
+            bind!(extrude, faces::extrude[shape=edge, x=option_number, y=option_number, z=option_number, twist=option_number, scale_top=option_number], Category::ThreeD, "extrude a face into a 3D shape, optionally twisted (degrees) and scaled towards the top"),
+            bind!(extrude, faces::extrude_face[shape=face, x=option_number, y=option_number, z=option_number], Category::ThreeD, "extrude a face with holes into a 3D shape"),
             bind!(revolve, faces::revolve[shape=edge, x=option_number, y=option_number, z=option_number], Category::ThreeD, "extrude a face into a 3D shape around an axis"),
+            bind!(revolve, faces::revolve_face[shape=face, x=option_number, y=option_number, z=option_number], Category::ThreeD, "extrude a face with holes into a 3D shape around an axis"),
             bind!(cube, shapes::cube[x=option_number, y=option_number, z=option_number], Category::ThreeD, "create a cube"),
             bind!(
                 sphere,
@@ -466,6 +527,7 @@ impl Default for Library {
             bind!(union, shapes::union_shape[left=shape, right=shape], Category::ThreeD, "combine two shapes"),
             bind!(chamfer, shapes::chamfer[shape=shape, radius=number], Category::ThreeD, "chamfer edges"),
             bind!(fillet, shapes::fillet[shape=shape, radius=number], Category::ThreeD, "fillet edges"),
+            bind!(offset, shapes::offset[shape=shape, distance=number], Category::ThreeD, "grow or shrink a shape uniformly"),
             bind!(difference, shapes::difference[left=shape, right=shape], Category::ThreeD, "cut one shape out of another"),
             bind!(intersect, shapes::intersect[left=shape, right=shape], Category::ThreeD, "intersection between two shapes"),
             bind!(translate, shapes::translate[shape=shape, x=option_number, y=option_number, z=option_number], Category::ThreeD, "move a shape"),
@@ -510,16 +572,21 @@ impl Display for Library {
 
         write!(
             f,
-            r"
+            r#"
 ## Syntax
 - `var name = value;` create a variable called name that stores value
 - `value;` draw the value, each script can only draw one thing
 - `b(name=a)` pass a into the name parameter of function b
 - `a ->name b()` pipe a into the name parameter of function b
 - `./file(name=a)` run a file as if it were a function
+- `./file.json()` load a .json, .toml, .csv or .ini file as numbers, text, lists and records, or a .stl file as a shape
+- `import "./file.ds" as name;` use the variables and functions of a file as name.variable, names starting with _ stay private
+- `<lib>/file(name=a)` run a file from the lib directory found in --lib or DSLCAD_PATH, also works with import
 - `model.data` access data of a model
 - `list[5]` get the fifth item of a list
 - `if a: something() else: something_else();` test a and follow one branch depending on the value
+- `assert a, "message";` stop with the message unless a is true
+- `10mm`, `2in`, `45deg` numbers with units (um, mm, cm, m, in, ft, deg, rad), lengths are converted to the model unit
 
 ## Lists
 - `[1,2,3]` make a list with three numbers
@@ -549,8 +616,9 @@ impl Display for Library {
 ## Properties
 - `point.{{x,y,z}}` properties of points
 - `2d_value.{{center}}` properties of 2D objects
-- `3d_value.{{center}}` properties of 3D objects
-"
+- `face.{{center,area}}` properties of faces
+- `3d_value.{{center,volume,min,max,size}}` properties of 3D objects, the bounding box is measured on a mesh
+"#
         )?;
 
         let mut to_print = self.signatures.clone();
@@ -625,8 +693,9 @@ impl Debug for Signature {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::runtime::ScriptInstance;
     use dslcad_occt::Point;
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     #[test]
     fn it_can_create_library() {
@@ -715,7 +784,7 @@ pub mod tests {
             .find(CallSignature::new(
                 "test",
                 vec![
-                    ArgValue::Named("a", Value::Point(Rc::new(Point::default()))),
+                    ArgValue::Named("a", Value::Point(Arc::new(Point::default()))),
                     ArgValue::Named("b", Value::Number(1.)),
                 ],
             ))
@@ -723,6 +792,38 @@ pub mod tests {
         call(&args).expect("called wrong handler");
     }
 
+    #[test]
+    fn it_prefers_overloads_without_conversions() {
+        let lib = Library::from_signatures(vec![
+            bind!(test, single[a = number], Category::Math, ""),
+            bind!(test, many[a = list], Category::Math, ""),
+        ]);
+        let (call, args) = lib
+            .find(CallSignature::new(
+                "test",
+                vec![ArgValue::Named("a", Value::List(vec![Value::Number(1.)]))],
+            ))
+            .expect("couldnt find method");
+        assert_eq!(Ok(2.), call(&args).unwrap().to_number());
+    }
+
+    #[test]
+    fn it_prefers_unwrapping_scripts_over_conversions() {
+        let lib = Library::from_signatures(vec![
+            bind!(test, single[a = number], Category::Math, ""),
+            bind!(test, many[a = list], Category::Math, ""),
+        ]);
+        let list = Value::List(vec![Value::Number(1.)]);
+        let script = ScriptInstance::new(vec![list], HashMap::new()).unwrap();
+        let (call, args) = lib
+            .find(CallSignature::new(
+                "test",
+                vec![ArgValue::Named("a", Value::Script(Arc::new(script)))],
+            ))
+            .expect("couldnt find method");
+        assert_eq!(Ok(2.), call(&args).unwrap().to_number());
+    }
+
     fn one(_a: f64, _b: f64) -> Result<Value, RuntimeError> {
         Ok(Value::Number(0.0))
     }
@@ -730,4 +831,12 @@ pub mod tests {
     fn two(_a: &Point, _b: f64) -> Result<Value, RuntimeError> {
         Ok(Value::Number(0.0))
     }
+
+    fn single(_a: f64) -> Result<Value, RuntimeError> {
+        Ok(Value::Number(1.0))
+    }
+
+    fn many(_a: &[Value]) -> Result<Value, RuntimeError> {
+        Ok(Value::Number(2.0))
+    }
 }
@@ -1,9 +1,9 @@
-use crate::runtime::{RuntimeError, Value};
-use dslcad_occt::{Axis, DsShape, Edge, Point, Shape, Wire, WireFactory};
-use std::rc::Rc;
+use crate::runtime::{RuntimeError, Type, Value};
+use dslcad_occt::{Axis, DsShape, Edge, Face, JoinType, Point, Shape, Wire, WireFactory};
+use std::sync::Arc;
 
 pub fn point(x: Option<f64>, y: Option<f64>, z: Option<f64>) -> Result<Value, RuntimeError> {
-    Ok(Value::Point(Rc::new(Point::new(
+    Ok(Value::Point(Arc::new(Point::new(
         x.unwrap_or(0.0),
         y.unwrap_or(0.0),
         z.unwrap_or(0.0),
@@ -13,7 +13,7 @@ pub fn point(x: Option<f64>, y: Option<f64>, z: Option<f64>) -> Result<Value, Ru
 pub fn line(start: &Point, end: &Point) -> Result<Value, RuntimeError> {
     let mut edge = WireFactory::new();
     edge.add_edge(&Edge::new_line(start, end)?);
-    Ok(Value::Line(Rc::new(edge.build()?)))
+    Ok(Value::Line(Arc::new(edge.build()?)))
 }
 
 pub fn arc(start: &Point, center: &Point, end: &Point) -> Result<Value, RuntimeError> {
@@ -23,7 +23,7 @@ pub fn arc(start: &Point, center: &Point, end: &Point) -> Result<Value, RuntimeE
 
     let mut edge = WireFactory::new();
     edge.add_edge(&Edge::new_arc(start, center, end)?);
-    Ok(Value::Line(Rc::new(edge.build()?)))
+    Ok(Value::Line(Arc::new(edge.build()?)))
 }
 
 pub fn square(x: Option<f64>, y: Option<f64>) -> Result<Value, RuntimeError> {
@@ -42,7 +42,7 @@ pub fn square(x: Option<f64>, y: Option<f64>) -> Result<Value, RuntimeError> {
     edge.add_edge(&Edge::new_line(&c, &d)?);
     edge.add_edge(&Edge::new_line(&d, &a)?);
 
-    Ok(Value::Line(Rc::new(edge.build()?)))
+    Ok(Value::Line(Arc::new(edge.build()?)))
 }
 
 pub fn circle(radius: Option<f64>) -> Result<Value, RuntimeError> {
@@ -61,16 +61,109 @@ pub fn circle(radius: Option<f64>) -> Result<Value, RuntimeError> {
     edge.add_edge(&Edge::new_arc(&a, &b, &c)?);
     edge.add_edge(&Edge::new_arc(&c, &d, &a)?);
 
-    Ok(Value::Line(Rc::new(edge.build()?)))
+    Ok(Value::Line(Arc::new(edge.build()?)))
 }
 
+// maximum rotation between two sections of a twisted extrusion
+const TWIST_STEP_DEGREES: f64 = 15.0;
+
 pub fn extrude(
     shape: &Wire,
     x: Option<f64>,
     y: Option<f64>,
     z: Option<f64>,
+    twist: Option<f64>,
+    scale_top: Option<f64>,
 ) -> Result<Value, RuntimeError> {
-    Ok(Value::Shape(Rc::new(Shape::extrude(
+    let (x, y, z) = (x.unwrap_or(0.0), y.unwrap_or(0.0), z.unwrap_or(0.0));
+    let twist = twist.unwrap_or(0.0);
+    let scale_top = scale_top.unwrap_or(1.0);
+
+    if scale_top <= 0.0 {
+        return Err(RuntimeError::InvalidArgument {
+            name: String::from("scale_top"),
+            value: scale_top.to_string(),
+            expected: String::from("a number greater than 0"),
+        });
+    }
+
+    if twist == 0.0 && scale_top == 1.0 {
+        return Ok(Value::Shape(Arc::new(Shape::extrude(shape, x, y, z)?)));
+    }
+
+    let length = (x * x + y * y + z * z).sqrt();
+    if length == 0.0 {
+        return Err(RuntimeError::UnsetParameter(String::from("x, y, or z")));
+    }
+    let axis = [x / length, y / length, z / length];
+    // closed profiles turn about their area centroid, open ones about the middle of the path
+    let center = match Face::from_wire(shape) {
+        Ok(face) => face.center_of_mass(),
+        Err(_) => shape.center_of_mass(),
+    };
+    let center = [center.x(), center.y(), center.z()];
+
+    let steps = (twist.abs() / TWIST_STEP_DEGREES).ceil().max(1.0) as usize;
+    let sections = (0..=steps)
+        .map(|step| {
+            let t = step as f64 / steps as f64;
+            let scale = 1.0 + (scale_top - 1.0) * t;
+            let offset = [x * t, y * t, z * t];
+            shape.transform(&section_transform(axis, center, twist * t, scale, offset))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Value::Shape(Arc::new(Shape::loft(&sections)?)))
+}
+
+/// 3 x 4 matrix that rotates `degrees` about `axis` and scales about `center`, then moves the
+/// result by `offset`.
+fn section_transform(
+    axis: [f64; 3],
+    center: [f64; 3],
+    degrees: f64,
+    scale: f64,
+    offset: [f64; 3],
+) -> [f64; 12] {
+    let [ux, uy, uz] = axis;
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let rotation = [
+        [
+            cos + ux * ux * (1. - cos),
+            ux * uy * (1. - cos) - uz * sin,
+            ux * uz * (1. - cos) + uy * sin,
+        ],
+        [
+            uy * ux * (1. - cos) + uz * sin,
+            cos + uy * uy * (1. - cos),
+            uy * uz * (1. - cos) - ux * sin,
+        ],
+        [
+            uz * ux * (1. - cos) - uy * sin,
+            uz * uy * (1. - cos) + ux * sin,
+            cos + uz * uz * (1. - cos),
+        ],
+    ];
+
+    let mut matrix = [0.0; 12];
+    for row in 0..3 {
+        let mut moved_center = 0.0;
+        for col in 0..3 {
+            matrix[row * 4 + col] = rotation[row][col] * scale;
+            moved_center += matrix[row * 4 + col] * center[col];
+        }
+        matrix[row * 4 + 3] = center[row] - moved_center + offset[row];
+    }
+    matrix
+}
+
+pub fn extrude_face(
+    shape: &Face,
+    x: Option<f64>,
+    y: Option<f64>,
+    z: Option<f64>,
+) -> Result<Value, RuntimeError> {
+    Ok(Value::Shape(Arc::new(Shape::extrude_face(
         shape,
         x.unwrap_or(0.0),
         y.unwrap_or(0.0),
@@ -83,6 +176,15 @@ pub fn revolve(
     x: Option<f64>,
     y: Option<f64>,
     z: Option<f64>,
+) -> Result<Value, RuntimeError> {
+    revolve_face(&Face::from_wire(shape)?, x, y, z)
+}
+
+pub fn revolve_face(
+    shape: &Face,
+    x: Option<f64>,
+    y: Option<f64>,
+    z: Option<f64>,
 ) -> Result<Value, RuntimeError> {
     let (axis, angle) = if let Some(x) = x {
         (Axis::X, x)
@@ -94,16 +196,28 @@ pub fn revolve(
         return Err(RuntimeError::UnsetParameter(String::from("x, y, or z")));
     };
 
-    Ok(Value::Shape(Rc::new(Shape::extrude_rotate(
+    Ok(Value::Shape(Arc::new(Shape::extrude_rotate_face(
         shape, axis, angle,
     )?)))
 }
 
 pub fn union_edge(left: &Wire, right: &Wire) -> Result<Value, RuntimeError> {
     let mut edge = WireFactory::new();
-    edge.add_wire(left);
-    edge.add_wire(right);
-    Ok(Value::Line(Rc::new(edge.build()?)))
+    edge.add_wire(left)?;
+    edge.add_wire(right)?;
+    Ok(Value::Line(Arc::new(edge.build()?)))
+}
+
+pub fn union_face(left: &Face, right: &Face) -> Result<Value, RuntimeError> {
+    Ok(Value::Face(Arc::new(left.fuse(right)?)))
+}
+
+pub fn difference(left: &Face, right: &Face) -> Result<Value, RuntimeError> {
+    Ok(Value::Face(Arc::new(left.cut(right)?)))
+}
+
+pub fn intersect(left: &Face, right: &Face) -> Result<Value, RuntimeError> {
+    Ok(Value::Face(Arc::new(left.intersect(right)?)))
 }
 
 pub fn face(parts: &[Value]) -> Result<Value, RuntimeError> {
@@ -131,30 +245,39 @@ pub fn face(parts: &[Value]) -> Result<Value, RuntimeError> {
         }
 
         if let Ok(line) = point.to_line() {
-            edge.add_wire(&line);
+            edge.add_wire(&line)?;
         }
     }
 
-    Ok(Value::Line(Rc::new(edge.build()?)))
+    Ok(Value::Line(Arc::new(edge.build()?)))
+}
+
+pub fn face_with_holes(outer: &Wire, holes: &[Value]) -> Result<Value, RuntimeError> {
+    let holes = holes
+        .iter()
+        .map(|hole| hole.to_line())
+        .collect::<Result<Vec<_>, _>>()?;
+    let holes: Vec<&Wire> = holes.iter().map(|hole| hole.as_ref()).collect();
+    Ok(Value::Face(Arc::new(Face::new(outer, &holes)?)))
 }
 
-fn start_point(value: &Value) -> Result<Rc<Point>, RuntimeError> {
+fn start_point(value: &Value) -> Result<Arc<Point>, RuntimeError> {
     if let Ok(point) = value.to_point() {
         Ok(point.clone())
     } else if let Ok(edge) = value.to_line() {
-        Ok(Rc::new(edge.start()?.unwrap()))
+        Ok(Arc::new(edge.start()?.unwrap()))
     } else {
-        Err(RuntimeError::UnexpectedType())
+        Err(value.unexpected(Type::Point))
     }
 }
 
-fn end_point(value: &Value) -> Result<Rc<Point>, RuntimeError> {
+fn end_point(value: &Value) -> Result<Arc<Point>, RuntimeError> {
     if let Ok(point) = value.to_point() {
         Ok(point.clone())
     } else if let Ok(edge) = value.to_line() {
-        Ok(Rc::new(edge.end()?.unwrap()))
+        Ok(Arc::new(edge.end()?.unwrap()))
     } else {
-        Err(RuntimeError::UnexpectedType())
+        Err(value.unexpected(Type::Point))
     }
 }
 
@@ -164,7 +287,7 @@ pub fn translate(
     y: Option<f64>,
     z: Option<f64>,
 ) -> Result<Value, RuntimeError> {
-    Ok(Value::Line(Rc::new(Wire::translate(
+    Ok(Value::Line(Arc::new(Wire::translate(
         shape,
         &Point::new(x.unwrap_or(0.0), y.unwrap_or(0.0), z.unwrap_or(0.0)),
     )?)))
@@ -173,7 +296,7 @@ pub fn translate(
 pub fn rotate(shape: &Wire, angle: Option<f64>) -> Result<Value, RuntimeError> {
     let shape = Wire::rotate(shape, Axis::Z, angle.unwrap_or(0.0))?;
 
-    Ok(Value::Line(Rc::new(shape)))
+    Ok(Value::Line(Arc::new(shape)))
 }
 
 pub fn rotate_3d(
@@ -186,11 +309,11 @@ pub fn rotate_3d(
     let shape = Wire::rotate(&shape, Axis::Y, y.unwrap_or(0.0))?;
     let shape = Wire::rotate(&shape, Axis::Z, z.unwrap_or(0.0))?;
 
-    Ok(Value::Line(Rc::new(shape)))
+    Ok(Value::Line(Arc::new(shape)))
 }
 
 pub fn scale(shape: &Wire, size: f64) -> Result<Value, RuntimeError> {
-    Ok(Value::Line(Rc::new(Wire::scale(shape, size)?)))
+    Ok(Value::Line(Arc::new(Wire::scale(shape, size)?)))
 }
 
 pub fn center(
@@ -206,6 +329,114 @@ pub fn center(
     translate(shape, Some(x), Some(y), Some(z))
 }
 
-pub fn offset(shape: &Wire, distance: f64) -> Result<Value, RuntimeError> {
-    Ok(shape.offset(distance)?.into())
+pub fn offset(shape: &Wire, distance: f64, join: Option<String>) -> Result<Value, RuntimeError> {
+    Ok(shape.offset(distance, join_type(join)?)?.into())
+}
+
+fn join_type(join: Option<String>) -> Result<JoinType, RuntimeError> {
+    match join.as_deref() {
+        None | Some("arc") => Ok(JoinType::Arc),
+        Some("tangent") => Ok(JoinType::Tangent),
+        Some("intersection") => Ok(JoinType::Intersection),
+        Some(other) => Err(RuntimeError::InvalidArgument {
+            name: String::from("join"),
+            value: other.to_string(),
+            expected: String::from("arc, tangent or intersection"),
+        }),
+    }
+}
+
+pub fn translate_face(
+    shape: &Face,
+    x: Option<f64>,
+    y: Option<f64>,
+    z: Option<f64>,
+) -> Result<Value, RuntimeError> {
+    Ok(Value::Face(Arc::new(shape.translate(&Point::new(
+        x.unwrap_or(0.0),
+        y.unwrap_or(0.0),
+        z.unwrap_or(0.0),
+    ))?)))
+}
+
+pub fn rotate_face(shape: &Face, angle: Option<f64>) -> Result<Value, RuntimeError> {
+    Ok(Value::Face(Arc::new(
+        shape.rotate(Axis::Z, angle.unwrap_or(0.0))?,
+    )))
+}
+
+pub fn scale_face(shape: &Face, size: f64) -> Result<Value, RuntimeError> {
+    Ok(Value::Face(Arc::new(shape.scale(size)?)))
+}
+
+pub fn center_face(
+    shape: &Face,
+    x: Option<bool>,
+    y: Option<bool>,
+    z: Option<bool>,
+) -> Result<Value, RuntimeError> {
+    let center = shape.center_of_mass();
+    let x = if x.unwrap_or(true) { -center.x() } else { 0.0 };
+    let y = if y.unwrap_or(true) { -center.y() } else { 0.0 };
+    let z = if z.unwrap_or(true) { -center.z() } else { 0.0 };
+    translate_face(shape, Some(x), Some(y), Some(z))
+}
+
+pub fn offset_face(
+    shape: &Face,
+    distance: f64,
+    join: Option<String>,
+) -> Result<Value, RuntimeError> {
+    Ok(shape.offset(distance, join_type(join)?)?.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(matrix: &[f64; 12], point: [f64; 3]) -> [f64; 3] {
+        let mut result = [0.0; 3];
+        for (row, value) in result.iter_mut().enumerate() {
+            *value = matrix[row * 4 + 3]
+                + (0..3)
+                    .map(|col| matrix[row * 4 + col] * point[col])
+                    .sum::<f64>();
+        }
+        result
+    }
+
+    fn assert_near(expected: [f64; 3], actual: [f64; 3]) {
+        for (e, a) in expected.iter().zip(actual) {
+            assert!(
+                (e - a).abs() < 1e-9,
+                "expected {expected:?} but found {actual:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_twists_sections_about_their_center() {
+        let matrix = section_transform([0., 0., 1.], [1., 1., 0.], 90., 0.5, [0., 0., 10.]);
+
+        assert_near([1., 1., 10.], apply(&matrix, [1., 1., 0.]));
+        assert_near([1., 1.5, 10.], apply(&matrix, [2., 1., 0.]));
+    }
+
+    #[test]
+    fn it_twists_sections_about_the_extrusion_direction() {
+        let matrix = section_transform([1., 0., 0.], [0., 0., 0.], 90., 1., [5., 0., 0.]);
+
+        assert_near([5., 0., 1.], apply(&matrix, [0., 1., 0.]));
+    }
+
+    #[test]
+    fn it_rejects_flat_tops() {
+        let shape = Wire::from_edge(
+            &Edge::new_line(&Point::new(0., 0., 0.), &Point::new(1., 0., 0.)).unwrap(),
+        );
+        assert!(matches!(
+            extrude(&shape.unwrap(), None, None, Some(1.), None, Some(0.)),
+            Err(RuntimeError::InvalidArgument { .. })
+        ));
+    }
 }
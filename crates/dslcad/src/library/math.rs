@@ -1,6 +1,8 @@
 use crate::runtime::{RuntimeError, Value};
 use std::f64::consts::PI;
 
+const APPROX_TOLERANCE: f64 = 0.001;
+
 pub fn round(number: f64) -> Result<f64, RuntimeError> {
     Ok(number.round())
 }
@@ -53,6 +55,12 @@ pub fn equals(left: f64, right: f64) -> Result<Value, RuntimeError> {
     Ok(Value::Bool(left == right))
 }
 
+pub fn approx(left: f64, right: f64, tolerance: Option<f64>) -> Result<Value, RuntimeError> {
+    Ok(Value::Bool(
+        (left - right).abs() <= tolerance.unwrap_or(APPROX_TOLERANCE),
+    ))
+}
+
 pub fn not_equals(left: f64, right: f64) -> Result<Value, RuntimeError> {
     Ok(Value::Bool(left != right))
 }
@@ -47,6 +47,10 @@ pub fn fillet(shape: &Shape, radius: f64) -> Result<Value, RuntimeError> {
     Ok(Shape::fillet(shape, radius)?.into())
 }
 
+pub fn offset(shape: &Shape, distance: f64) -> Result<Value, RuntimeError> {
+    Ok(shape.offset(distance)?.into())
+}
+
 pub fn translate(
     shape: &Shape,
     x: Option<f64>,
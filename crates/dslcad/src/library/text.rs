@@ -1,4 +1,4 @@
-use crate::runtime::{RuntimeError, Value};
+use crate::runtime::{RuntimeError, Type, Value};
 use std::collections::HashMap;
 
 pub fn add(left: String, right: String) -> Result<String, RuntimeError> {
@@ -10,7 +10,7 @@ pub fn string(item: Value) -> Result<String, RuntimeError> {
         Value::Number(n) => Ok(n.to_string()),
         Value::Bool(n) => Ok(n.to_string()),
         Value::Text(n) => Ok(n),
-        _ => Err(RuntimeError::UnexpectedType()),
+        _ => Err(item.unexpected(Type::Text)),
     }
 }
 
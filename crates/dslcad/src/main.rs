@@ -1,25 +1,47 @@
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use dslcad::error_printer::ErrorPrinter;
 use dslcad::library::Library;
-use dslcad::parser::{DocumentParseError, ParseError};
+use dslcad::parameters::{parameters, Parameter};
+use dslcad::params_file::{self, ParamsFileError};
+use dslcad::parser::{DocumentParseError, Expression, ParseErrors};
+use dslcad::profiler::Profiler;
+use dslcad::project::{Argument, Format, Job, Project, ProjectError};
 use dslcad::reader::FsReader;
-use dslcad::runtime::{RuntimeError, WithStack};
-use dslcad::{eval, parse, parse_arguments, render};
+use dslcad::runtime::{Cache, RuntimeError, Value, WithStack};
+use dslcad::sweep::Sweep;
+use dslcad::testing::{discover, run_test, TestError};
+use dslcad::{
+    eval_with_cache, eval_with_profiler, parse_arguments_with_unit, parse_sweep,
+    parse_with_options, render, ParseOptions,
+};
 use dslcad_storage::protocol::{BincodeError, Render};
-use dslcad_storage::threemf::{ThreeMF, ThreeMFError};
+use dslcad_storage::threemf::{ThreeMF, ThreeMFError, Unit};
 use dslcad_viewer::PreviewHandle;
+use indexmap::IndexMap;
 use log::info;
 use std::env;
 use std::fs::File;
-use std::io::{stderr, Write};
-use std::path::Path;
+use std::io::{stderr, stdout, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Parser, Debug, Clone)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
+)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(required = true)]
     /// Source path to load
-    source: String,
+    source: Option<String>,
 
     #[cfg(feature = "preview")]
     #[arg(short, long)]
@@ -27,25 +49,99 @@ struct Args {
     preview: bool,
 
     #[arg(short, long)]
-    /// Arguments for the script (examples: "foo=5", "name=\"bob\"")
+    /// Arguments for the script (examples: "foo=5", "name=\"bob\"", "points=[1,2,3]"), values
+    /// separated by `|` such as "size=10|20" and ranges such as "wall=1..3 step 0.5" produce one
+    /// output per combination when rendering to files
     argument: Vec<String>,
 
+    #[arg(long, value_name = "FILE")]
+    /// JSON or TOML file of arguments for the script, --argument overrides its values
+    params: Option<PathBuf>,
+
     #[arg(short, long, default_value_t = 0.01)]
     /// Deflection used to calculate mesh (smaller = more detail)
     deflection: f64,
 
     #[arg(short, long, value_enum, default_value = "3mf")]
     /// Output file format
-    output: Output,
+    output: Format,
+
+    #[arg(long)]
+    /// Evaluate the script and its assertions without writing any output
+    check: bool,
+
+    #[arg(short, long)]
+    /// Unit of the model, numbers such as `2in` are converted to it [default: the unit of the
+    /// project or millimeter]
+    unit: Option<Unit>,
+
+    #[arg(long, value_name = "DIR")]
+    /// Directory to search for library paths such as `<std>/file`, before those in DSLCAD_PATH
+    lib: Vec<PathBuf>,
 
     #[arg(short, long)]
     /// Log filter
     log: Option<String>,
 
+    #[arg(long, value_name = "DIR")]
+    /// Directory used to persist evaluated geometry between runs, files unused for 30 days are
+    /// deleted
+    cache: Option<PathBuf>,
+
+    #[arg(long)]
+    #[cfg_attr(feature = "preview", arg(conflicts_with = "preview"))]
+    /// Print the time spent in the slowest statements and builtin calls
+    profile: bool,
+
+    #[arg(long, value_name = "FILE")]
+    #[cfg_attr(feature = "preview", arg(conflicts_with = "preview"))]
+    /// Write the time spent in every statement and builtin call as a Chrome trace
+    profile_trace: Option<PathBuf>,
+
+    #[arg(long, value_enum, default_value = "human", global = true)]
+    /// Format of error messages
+    message_format: MessageFormat,
+
     #[command(flatten)]
     cheatsheet: Cheatsheet,
 }
 
+#[derive(Subcommand, Debug, Clone)]
+enum Command {
+    /// Run the assertions in every `*_test.ds` file and report which pass
+    Test {
+        #[arg(default_value = ".")]
+        /// Test files or directories to search for them
+        paths: Vec<PathBuf>,
+
+        #[arg(long, value_name = "DIR")]
+        /// Directory to search for library paths such as `<std>/file`, before those in DSLCAD_PATH
+        lib: Vec<PathBuf>,
+    },
+    /// List the parameters of a script that can be set with --argument
+    Params {
+        /// Source path to load
+        source: String,
+
+        #[arg(long)]
+        /// Print the parameters as JSON
+        json: bool,
+
+        #[arg(long, value_name = "DIR")]
+        /// Directory to search for library paths such as `<std>/file`, before those in DSLCAD_PATH
+        lib: Vec<PathBuf>,
+    },
+    /// Render the targets listed in dslcad.toml into its output directory
+    Build {
+        /// Target to build, every target is built when omitted
+        target: Option<String>,
+
+        #[arg(long, value_name = "DIR")]
+        /// Directory to search for library paths such as `<std>/file`, before those in DSLCAD_PATH
+        lib: Vec<PathBuf>,
+    },
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 struct Cheatsheet {
@@ -54,12 +150,75 @@ struct Cheatsheet {
     cheatsheet: bool,
 }
 
-#[derive(Debug, Clone, Default, ValueEnum)]
-enum Output {
+/// Arguments for the script from `--argument` and the `--params` file.
+struct ScriptArguments {
+    arguments: Vec<String>,
+    params: IndexMap<String, Argument>,
+}
+
+impl ScriptArguments {
+    /// Every combination of values to render, `--argument` overrides values from the file.
+    fn sweep(&self, unit: Unit) -> Result<Sweep<'_>, CliError> {
+        let params = self.params.iter().map(|(name, argument)| {
+            (
+                name.as_str(),
+                Expression::Literal(argument.to_literal(), 0..0),
+            )
+        });
+        Ok(parse_sweep(self.arguments.iter().map(|i| i.as_str()), unit)?.with_defaults(params))
+    }
+
+    /// The values from the file that `--argument` does not set followed by `--argument`, all
+    /// written as `name=value`.
+    #[cfg(feature = "preview")]
+    fn into_strings(self) -> Vec<String> {
+        let is_given = |name: &str| {
+            self.arguments
+                .iter()
+                .filter_map(|argument| argument.split_once('='))
+                .any(|(given, _)| given.trim() == name)
+        };
+
+        let mut arguments: Vec<String> = self
+            .params
+            .iter()
+            .filter(|(name, _)| !is_given(name))
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        arguments.extend(self.arguments);
+        arguments
+    }
+}
+
+struct Profile {
+    report: bool,
+    trace: Option<PathBuf>,
+}
+
+impl Profile {
+    const REPORT_LENGTH: usize = 20;
+
+    fn is_enabled(&self) -> bool {
+        self.report || self.trace.is_some()
+    }
+
+    fn write(&self, profiler: &Profiler) -> Result<(), CliError> {
+        if self.report {
+            profiler.write_report(stderr(), &FsReader, Self::REPORT_LENGTH)?;
+        }
+        if let Some(path) = &self.trace {
+            profiler.write_chrome_trace(File::create(path)?)?;
+            info!("profile written to {}", path.to_string_lossy());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+enum MessageFormat {
     #[default]
-    #[value(name = "3mf")]
-    ThreeMf,
-    Raw,
+    Human,
+    Json,
 }
 
 #[derive(Debug, Error)]
@@ -67,7 +226,7 @@ enum CliError {
     #[error(transparent)]
     ArgParse(#[from] DocumentParseError),
     #[error(transparent)]
-    Parse(#[from] ParseError),
+    Parse(#[from] ParseErrors),
     #[error(transparent)]
     Runtime(#[from] WithStack<RuntimeError>),
     #[error(transparent)]
@@ -80,6 +239,28 @@ enum CliError {
     Bincode(#[from] BincodeError),
     #[error(transparent)]
     Notify(#[from] notify::Error),
+    #[error(transparent)]
+    Project(#[from] ProjectError),
+    #[error(transparent)]
+    Params(#[from] ParamsFileError),
+}
+
+impl CliError {
+    /// Stable identifier of the error for tools that consume `--message-format json`.
+    fn code(&self) -> &'static str {
+        match self {
+            CliError::ArgParse(e) => e.code(),
+            CliError::Parse(_) => "Parse",
+            CliError::Runtime(e) => e.error.code(),
+            CliError::Render(e) => e.code(),
+            CliError::Io(_) => "Io",
+            CliError::ThreeMf(_) => "ThreeMf",
+            CliError::Bincode(_) => "Bincode",
+            CliError::Notify(_) => "Notify",
+            CliError::Project(_) => "Project",
+            CliError::Params(_) => "Params",
+        }
+    }
 }
 
 fn main() {
@@ -89,18 +270,82 @@ fn main() {
                 env_logger::builder().parse_filters(log).init();
             }
 
+            match &args.command {
+                Some(Command::Test { paths, lib }) => {
+                    return exit_on_failure(
+                        run_tests(paths, lib, args.message_format),
+                        args.message_format,
+                    )
+                }
+                Some(Command::Params { source, json, lib }) => {
+                    return exit_on_failure(run_params(source, *json, lib), args.message_format)
+                }
+                Some(Command::Build { target, lib }) => {
+                    return exit_on_failure(
+                        run_build(target.as_deref(), lib, args.message_format),
+                        args.message_format,
+                    )
+                }
+                None => {}
+            }
+            let source = args
+                .source
+                .expect("source is required without a subcommand");
+            let options = match project_for(Path::new(&source)) {
+                Ok(project) => ParseOptions {
+                    unit: args
+                        .unit
+                        .or(project.as_ref().map(|project| project.unit))
+                        .unwrap_or_default(),
+                    library_paths: library_paths(&args.lib, project.as_ref()),
+                },
+                Err(e) => {
+                    handle_error(e, args.message_format, &mut stderr()).unwrap();
+                    std::process::exit(1);
+                }
+            };
+            let arguments = match args.params.as_deref().map(params_file::read).transpose() {
+                Ok(params) => ScriptArguments {
+                    arguments: args.argument,
+                    params: params.unwrap_or_default(),
+                },
+                Err(e) => {
+                    handle_error(e.into(), args.message_format, &mut stderr()).unwrap();
+                    std::process::exit(1);
+                }
+            };
+
             #[cfg(feature = "preview")]
             if args.preview {
-                if let Err(e) = render_to_preview(&args.source, args.argument, args.deflection) {
-                    handle_error(e, &mut stderr()).unwrap();
+                if let Err(e) = render_to_preview(
+                    &source,
+                    arguments.into_strings(),
+                    args.deflection,
+                    options,
+                    new_cache(args.cache.as_deref()),
+                ) {
+                    handle_error(e, args.message_format, &mut stderr()).unwrap();
+                    std::process::exit(1);
                 }
                 return;
             }
 
-            if let Err(e) =
-                render_to_file(&args.source, args.argument, args.deflection, args.output)
-            {
-                handle_error(e, &mut stderr()).unwrap();
+            let profile = Profile {
+                report: args.profile,
+                trace: args.profile_trace,
+            };
+            let output = (!args.check).then_some(args.output);
+            if let Err(e) = render_to_file(
+                &source,
+                arguments,
+                args.deflection,
+                output,
+                options,
+                new_cache(args.cache.as_deref()),
+                profile,
+            ) {
+                handle_error(e, args.message_format, &mut stderr()).unwrap();
+                std::process::exit(1);
             }
         }
         Err(e) => {
@@ -113,57 +358,353 @@ fn main() {
     }
 }
 
-fn handle_error(error: CliError, writer: &mut impl Write) -> Result<(), std::io::Error> {
+/// Exit with a failure status unless a command returned `Ok(true)`.
+fn exit_on_failure(result: Result<bool, CliError>, format: MessageFormat) {
+    match result {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(e) => {
+            handle_error(e, format, &mut stderr()).unwrap();
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The project that `path` belongs to, if there is a manifest in it or one of its parents.
+fn project_for(path: &Path) -> Result<Option<Project>, CliError> {
+    Ok(Project::find(&env::current_dir()?.join(path))?)
+}
+
+/// Library directories from `--lib`, then the project manifest, then the `DSLCAD_PATH`
+/// environment variable.
+fn library_paths(lib: &[PathBuf], project: Option<&Project>) -> Vec<PathBuf> {
+    let mut paths = lib.to_vec();
+    paths.extend(project.iter().flat_map(|project| project.library_paths()));
+    if let Some(path) = env::var_os("DSLCAD_PATH") {
+        paths.extend(env::split_paths(&path));
+    }
+    paths
+}
+
+/// Cached geometry that was not used for this long is deleted from the `--cache` directory.
+const CACHE_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+fn new_cache(directory: Option<&Path>) -> Cache {
+    match directory {
+        Some(directory) => {
+            let cache = Cache::new().with_directory(directory);
+            cache.prune_directory(CACHE_MAX_AGE);
+            cache
+        }
+        None => Cache::new(),
+    }
+}
+
+fn handle_error(
+    error: CliError,
+    format: MessageFormat,
+    writer: &mut impl Write,
+) -> Result<(), std::io::Error> {
     let printer = ErrorPrinter::new(FsReader);
 
-    match error {
-        CliError::Parse(parse_error) => printer.print_parse_error(writer, &parse_error),
-        CliError::Runtime(runtime_error) => printer.print_runtime_error(writer, &runtime_error),
-        _ => printer.print_error(writer, &error),
+    match (format, error) {
+        (MessageFormat::Human, CliError::Parse(parse_errors)) => {
+            printer.print_parse_errors(writer, &parse_errors)
+        }
+        (MessageFormat::Human, CliError::Runtime(runtime_error)) => {
+            printer.print_runtime_error(writer, &runtime_error)
+        }
+        (MessageFormat::Human, error) => printer.print_error(writer, &error),
+        (MessageFormat::Json, CliError::Parse(parse_errors)) => {
+            printer.print_parse_errors_json(writer, &parse_errors)
+        }
+        (MessageFormat::Json, CliError::Runtime(runtime_error)) => {
+            printer.print_runtime_error_json(writer, &runtime_error)
+        }
+        (MessageFormat::Json, error) => printer.print_error_json(writer, error.code(), &error),
     }
 }
 
+/// Run every test file found in `paths`, returning whether all of them passed. With JSON messages
+/// only the failures are printed, as diagnostics.
+fn run_tests(
+    paths: &[PathBuf],
+    lib: &[PathBuf],
+    message_format: MessageFormat,
+) -> Result<bool, CliError> {
+    let project = project_for(Path::new("."))?;
+    let options = ParseOptions {
+        unit: project
+            .as_ref()
+            .map(|project| project.unit)
+            .unwrap_or_default(),
+        library_paths: library_paths(lib, project.as_ref()),
+    };
+
+    let mut files = Vec::new();
+    for path in paths {
+        files.extend(discover(path)?);
+    }
+
+    let printer = ErrorPrinter::new(FsReader);
+    let mut out = stdout();
+    let human = matches!(message_format, MessageFormat::Human);
+    let (mut passed, mut failed) = (0, 0);
+    for file in &files {
+        let result = run_test(FsReader, file, &options);
+        if human {
+            writeln!(out, "{}", file.to_string_lossy())?;
+        }
+
+        for assertion in &result.assertions {
+            if assertion.passed {
+                passed += 1;
+                if human {
+                    writeln!(out, "  ok     {}", assertion.message)?;
+                }
+                continue;
+            }
+
+            failed += 1;
+            let error = WithStack {
+                error: RuntimeError::AssertionFailed(assertion.message.clone()),
+                stack: vec![assertion.frame.clone()],
+            };
+            if human {
+                writeln!(out, "  FAILED {}", assertion.message)?;
+                printer.print_runtime_error(&mut out, &error)?;
+            } else {
+                printer.print_runtime_error_json(&mut out, &error)?;
+            }
+        }
+
+        match (&result.error, message_format) {
+            (Some(TestError::Parse(errors)), MessageFormat::Human) => {
+                printer.print_parse_errors(&mut out, errors)?
+            }
+            (Some(TestError::Parse(errors)), MessageFormat::Json) => {
+                printer.print_parse_errors_json(&mut out, errors)?
+            }
+            (Some(TestError::Runtime(error)), MessageFormat::Human) => {
+                printer.print_runtime_error(&mut out, error)?
+            }
+            (Some(TestError::Runtime(error)), MessageFormat::Json) => {
+                printer.print_runtime_error_json(&mut out, error)?
+            }
+            (None, _) => {}
+        }
+        failed += result.error.is_some() as usize;
+    }
+
+    if human {
+        writeln!(out, "\ntest result: {passed} passed; {failed} failed")?;
+    }
+    Ok(failed == 0)
+}
+
+/// Print the parameters of the root document at `source`.
+fn run_params(source: &str, json: bool, lib: &[PathBuf]) -> Result<bool, CliError> {
+    let project = project_for(Path::new(source))?;
+    let options = ParseOptions {
+        unit: project
+            .as_ref()
+            .map(|project| project.unit)
+            .unwrap_or_default(),
+        library_paths: library_paths(lib, project.as_ref()),
+    };
+    let ast = parse_with_options(source.to_string(), &options)?;
+    let parameters = parameters(&ast);
+
+    let mut out = stdout();
+    if json {
+        #[derive(serde::Serialize)]
+        struct Document<'a> {
+            parameters: &'a [Parameter],
+        }
+
+        let document = Document {
+            parameters: &parameters,
+        };
+        serde_json::to_writer_pretty(&mut out, &document).map_err(std::io::Error::from)?;
+        writeln!(out)?;
+        return Ok(true);
+    }
+
+    for parameter in &parameters {
+        write!(out, "{}", parameter.name)?;
+        if let Some(kind) = parameter.kind {
+            write!(out, ": {kind}")?;
+        }
+        match &parameter.default {
+            Some(default) => writeln!(out, " = {default}")?,
+            None => writeln!(out, " (required)")?,
+        }
+        for line in parameter.doc.iter().flat_map(|doc| doc.lines()) {
+            writeln!(out, "    {line}")?;
+        }
+    }
+    Ok(true)
+}
+
 fn render_to_file(
     source: &String,
-    arguments: Vec<String>,
+    arguments: ScriptArguments,
     deflection: f64,
-    output: Output,
+    output: Option<Format>,
+    options: ParseOptions,
+    cache: Cache,
+    profile: Profile,
 ) -> Result<(), CliError> {
-    let arguments = parse_arguments(arguments.iter().map(|i| i.as_str()))?;
-    let eval_result = eval(parse(source.clone())?, arguments)?;
+    let sweep = arguments.sweep(options.unit)?;
+    let ast = parse_with_options(source.to_string(), &options)?;
+    let profiler = profile.is_enabled().then(Profiler::new);
+
+    let mut result = Ok(());
+    for variant in sweep.variants() {
+        let eval_result = match &profiler {
+            Some(profiler) => eval_with_profiler(&ast, variant.arguments, &cache, profiler),
+            None => eval_with_cache(&ast, variant.arguments, &cache),
+        };
+        result = eval_result.map_err(CliError::from).and_then(|value| {
+            write_variant(source, &variant.name, value, deflection, output, &options)
+        });
+        if result.is_err() {
+            break;
+        }
+    }
 
-    let text_output = eval_result.to_text().unwrap_or_default();
+    if let Some(profiler) = &profiler {
+        profile.write(profiler)?;
+    }
+    result
+}
+
+/// Print the text output of one evaluation and write its geometry, named after `source` and
+/// the `variant` of a sweep.
+fn write_variant(
+    source: &str,
+    variant: &str,
+    value: Value,
+    deflection: f64,
+    output: Option<Format>,
+    options: &ParseOptions,
+) -> Result<(), CliError> {
+    let text_output = value.to_text().unwrap_or_default();
     if !text_output.is_empty() {
         println!("{}", &text_output);
     }
 
-    let cwd = env::current_dir()?;
-    let file = Path::new(source).file_stem().unwrap();
+    let Some(output) = output else {
+        info!("checks passed for {source} {variant}");
+        return Ok(());
+    };
 
-    let outfile = match output {
-        Output::ThreeMf => {
-            let render = render(eval_result, deflection)?;
+    let mut file = Path::new(source)
+        .file_stem()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    if !variant.is_empty() {
+        file = format!("{file}-{variant}");
+    }
+    let outfile = env::current_dir()?.join(format!("{file}.{}", output.extension()));
+    write_render(render(value, deflection)?, output, &outfile, options.unit)?;
 
-            let outpath = cwd.join(format!("{}.3mf", file.to_string_lossy()));
-            let threemf: ThreeMF = render.into();
-            let out = File::create(&outpath)?;
-            threemf.write_to_zip(out)?;
-            outpath
-        }
-        Output::Raw => {
-            let render = render(eval_result, deflection)?;
+    info!("output written to {}", outfile.to_string_lossy());
+
+    Ok(())
+}
 
-            let outpath = cwd.join(format!("{}.bin", file.to_string_lossy()));
+fn write_render(render: Render, format: Format, path: &Path, unit: Unit) -> Result<(), CliError> {
+    match format {
+        Format::ThreeMf => {
+            let mut threemf: ThreeMF = render.into();
+            threemf.set_unit(unit);
+            threemf.write_to_zip(File::create(path)?)?;
+        }
+        Format::Raw => {
             let raw: Vec<u8> = render.try_into()?;
-            let mut out = File::create(&outpath)?;
-            out.write_all(&raw)?;
-            outpath
+            File::create(path)?.write_all(&raw)?;
         }
+    }
+    Ok(())
+}
+
+/// Build every job of `target` in the project found in the working directory, returning whether
+/// all of them succeeded.
+fn run_build(
+    target: Option<&str>,
+    lib: &[PathBuf],
+    message_format: MessageFormat,
+) -> Result<bool, CliError> {
+    let project = project_for(Path::new("."))?
+        .ok_or_else(|| ProjectError::NotFound(env::current_dir().unwrap_or_default()))?;
+    let jobs = project.jobs(target)?;
+    let options = ParseOptions {
+        unit: project.unit,
+        library_paths: library_paths(lib, Some(&project)),
     };
+    let output = project.output_directory();
+    std::fs::create_dir_all(&output)?;
+
+    let cache = Cache::new();
+    let results = build_jobs(&jobs, |job| build_job(job, &options, &output, &cache));
+
+    let mut failed = 0;
+    for (job, result) in jobs.iter().zip(results) {
+        match result {
+            Ok(files) => {
+                for file in files {
+                    println!("built {}", file.to_string_lossy());
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                if matches!(message_format, MessageFormat::Human) {
+                    eprintln!("failed to build {}", job.name);
+                }
+                handle_error(e, message_format, &mut stderr())?;
+            }
+        }
+    }
 
-    info!("output written to {}", outfile.to_string_lossy());
+    println!(
+        "\nbuild result: {} built; {failed} failed",
+        jobs.len() - failed
+    );
+    Ok(failed == 0)
+}
 
-    Ok(())
+fn build_job(
+    job: &Job,
+    options: &ParseOptions,
+    output: &Path,
+    cache: &Cache,
+) -> Result<Vec<PathBuf>, CliError> {
+    let ast = parse_with_options(job.source.to_string_lossy().to_string(), options)?;
+    let render = render(
+        eval_with_cache(&ast, job.arguments(), cache)?,
+        job.deflection,
+    )?;
+
+    let mut files = Vec::new();
+    for format in &job.formats {
+        let path = output.join(format!("{}.{}", job.name, format.extension()));
+        write_render(render.clone(), *format, &path, options.unit)?;
+        files.push(path);
+    }
+    Ok(files)
+}
+
+#[cfg(feature = "rayon")]
+fn build_jobs<T: Send>(jobs: &[Job], build: impl Fn(&Job) -> T + Sync + Send) -> Vec<T> {
+    use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+    jobs.par_iter().map(build).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn build_jobs<T>(jobs: &[Job], build: impl Fn(&Job) -> T) -> Vec<T> {
+    jobs.iter().map(build).collect()
 }
 
 #[cfg(feature = "preview")]
@@ -171,9 +712,11 @@ fn render_to_preview(
     source: &str,
     arguments: Vec<String>,
     deflection: f64,
+    options: ParseOptions,
+    cache: Cache,
 ) -> Result<(), CliError> {
     use dslcad::parser::{Ast, DocId};
-    use dslcad_viewer::Preview;
+    use dslcad_viewer::{HostMessage, Preview};
     use notify::{recommended_watcher, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
     use std::sync::{Arc, Mutex};
 
@@ -189,32 +732,63 @@ fn render_to_preview(
         });
     }
 
+    /// Parameters of the root document with the values they are set to by `arguments`.
+    fn preview_parameters(ast: &Ast, arguments: &[String]) -> Vec<dslcad_viewer::Parameter> {
+        parameters(ast)
+            .into_iter()
+            .map(|parameter| {
+                let prefix = format!("{}=", parameter.name);
+                let argument = arguments.iter().find_map(|a| a.strip_prefix(&prefix));
+                dslcad_viewer::Parameter {
+                    value: argument
+                        .or(parameter.default.as_deref())
+                        .unwrap_or_default()
+                        .to_string(),
+                    is_argument: argument.is_some(),
+                    name: parameter.name,
+                    doc: parameter.doc,
+                }
+            })
+            .collect()
+    }
+
     fn render_with_watcher(
+        handle: &PreviewHandle,
         source: &str,
         arguments: &[String],
         deflection: f64,
+        options: &ParseOptions,
         watch: Arc<Mutex<Option<RecommendedWatcher>>>,
+        cache: &Cache,
     ) -> Result<Render, CliError> {
-        let ast = parse(source.to_string())?;
+        let ast = parse_with_options(source.to_string(), options)?;
         add_files_to_watch(watch, &ast);
-        let arguments = parse_arguments(arguments.iter().map(|i| i.as_str()))?;
-        let render = render(eval(ast, arguments)?, deflection)?;
+        handle.show_parameters(preview_parameters(&ast, arguments));
+        let arguments =
+            parse_arguments_with_unit(arguments.iter().map(|i| i.as_str()), options.unit)?;
+        let render = render(eval_with_cache(&ast, arguments, cache)?, deflection)?;
+        cache.evict_untouched();
         Ok(render)
     }
 
     fn render_to_handle(
         handle: PreviewHandle,
         source: &str,
-        arguments: &[String],
+        arguments: &Mutex<Vec<String>>,
         deflection: f64,
+        options: &ParseOptions,
         watch: Arc<Mutex<Option<RecommendedWatcher>>>,
+        cache: &Cache,
     ) {
         handle.show_rendering();
-        match render_with_watcher(source, arguments, deflection, watch) {
+        let arguments = arguments.lock().unwrap().clone();
+        match render_with_watcher(
+            &handle, source, &arguments, deflection, options, watch, cache,
+        ) {
             Ok(render) => handle.show_render(render),
             Err(err) => {
                 let mut buffer = Vec::new();
-                handle_error(err, &mut buffer).unwrap();
+                handle_error(err, MessageFormat::Human, &mut buffer).unwrap();
                 handle.show_error(String::from_utf8(buffer).unwrap());
             }
         }
@@ -222,13 +796,17 @@ fn render_to_preview(
 
     let (preview, handle) = Preview::new();
     let watch = Arc::new(Mutex::new(None));
+    let cache = Arc::new(cache);
+    let arguments = Arc::new(Mutex::new(arguments));
 
     let watcher = {
-        let (source, arguments, watch, handle) = (
+        let (source, arguments, options, watch, handle, cache) = (
             source.to_string(),
             arguments.clone(),
+            options.clone(),
             watch.clone(),
             handle.clone(),
+            cache.clone(),
         );
         recommended_watcher(move |event| {
             if let Ok(notify::Event {
@@ -241,7 +819,9 @@ fn render_to_preview(
                     &source,
                     &arguments,
                     deflection,
+                    &options,
                     watch.clone(),
+                    &cache,
                 );
             }
         })?
@@ -254,7 +834,32 @@ fn render_to_preview(
 
     let source = source.to_string();
     std::thread::spawn(move || {
-        render_to_handle(handle, &source, &arguments, deflection, watch.clone())
+        render_to_handle(
+            handle.clone(),
+            &source,
+            &arguments,
+            deflection,
+            &options,
+            watch.clone(),
+            &cache,
+        );
+
+        while let Some(message) = handle.receive() {
+            match message {
+                HostMessage::Arguments(new_arguments) => {
+                    *arguments.lock().unwrap() = new_arguments;
+                    render_to_handle(
+                        handle.clone(),
+                        &source,
+                        &arguments,
+                        deflection,
+                        &options,
+                        watch.clone(),
+                        &cache,
+                    );
+                }
+            }
+        }
     });
 
     preview.open(Library::default().to_string());
@@ -0,0 +1,173 @@
+use crate::parser::{Argument, Ast, CallPath, Expression, Literal, Reference, Statement};
+use crate::runtime::Type;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A `var` in the root document, which `--argument` can set.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Parameter {
+    pub name: String,
+    /// Source of the default value, `None` when the parameter has to be set.
+    pub default: Option<String>,
+    /// Type of the default value when it can be told without running the script.
+    #[serde(rename = "type")]
+    pub kind: Option<Type>,
+    /// The `//` comment lines directly above the parameter, or after it on the same line.
+    pub doc: Option<String>,
+}
+
+impl Parameter {
+    pub fn required(&self) -> bool {
+        self.default.is_none()
+    }
+}
+
+/// Parameters of the root document in the order they are declared. Names starting with `_` are
+/// private and left out.
+pub fn parameters(ast: &Ast) -> Vec<Parameter> {
+    let source = ast.source(ast.root()).unwrap_or_default();
+    let mut types = HashMap::new();
+
+    let mut parameters = Vec::new();
+    for statement in ast.root_document() {
+        let Statement::Variable(variable, span) = statement else {
+            continue;
+        };
+
+        let kind = variable
+            .value
+            .as_ref()
+            .and_then(|value| infer_type(value, &types));
+        if let Some(kind) = kind {
+            types.insert(variable.name.as_str(), kind);
+        }
+        if variable.name.starts_with('_') {
+            continue;
+        }
+
+        parameters.push(Parameter {
+            name: variable.name.clone(),
+            default: variable
+                .value
+                .as_ref()
+                .map(|value| source[value.span().clone()].to_string()),
+            kind,
+            doc: doc_comment(source, span.start, span.end),
+        });
+    }
+    parameters
+}
+
+/// Type of `expression` from its literals and the parameters declared before it.
+fn infer_type(expression: &Expression, types: &HashMap<&str, Type>) -> Option<Type> {
+    match expression {
+        Expression::Literal(literal, _) => match literal {
+            Literal::Number(_) => Some(Type::Number),
+            Literal::Bool(_) => Some(Type::Bool),
+            Literal::Text(_) => Some(Type::Text),
+            Literal::List(_) => Some(Type::List),
+            Literal::Function(_) => Some(Type::Function),
+            Literal::Resource(_) => None,
+        },
+        Expression::Reference(Reference { name }, _) => types.get(name.as_str()).copied(),
+        Expression::Invocation(invocation, _) => {
+            let CallPath::Function(function) = &invocation.path else {
+                return None;
+            };
+            let Expression::Reference(Reference { name }, _) = function.as_ref() else {
+                return None;
+            };
+            let numbers = invocation.arguments.iter().all(|argument| {
+                let (Argument::Named(_, value) | Argument::Unnamed(value)) = argument;
+                infer_type(value, types) == Some(Type::Number)
+            });
+
+            match name.as_str() {
+                "not" | "and" | "or" | "equals" | "not_equals" | "less" | "less_or_equal"
+                | "greater" | "greater_or_equal" => Some(Type::Bool),
+                "add" | "subtract" | "multiply" | "divide" | "modulo" | "power" if numbers => {
+                    Some(Type::Number)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Comment lines directly above `start`, or the comment following `end` on the same line.
+fn doc_comment(source: &str, start: usize, end: usize) -> Option<String> {
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let mut lines = Vec::new();
+    if source[line_start..start].trim().is_empty() {
+        for line in source[..line_start].lines().rev() {
+            match line.trim().strip_prefix("//") {
+                Some(comment) => lines.push(comment),
+                None => break,
+            }
+        }
+        lines.reverse();
+    }
+
+    if lines.is_empty() {
+        let line_end = source[end..].find('\n').map_or(source.len(), |i| end + i);
+        if let Some(comment) = source[end..line_end].trim().strip_prefix("//") {
+            lines.push(comment);
+        }
+    }
+
+    let lines: Vec<_> = lines
+        .into_iter()
+        .map(|line| line.trim_start_matches('/').trim())
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{DocId, Parser};
+    use crate::tests::TestReader;
+
+    #[test]
+    fn it_lists_parameters() {
+        let code = "// Width of the part
+            // in millimeters
+            var width = 10;
+            var height; // Height of the part
+            var label = \"a\";
+
+            var _private = 1;
+            var half = width / 2;
+            var tall = height > width;
+            var shape = cube();
+            cube(x=width);";
+        let ast = Parser::new(TestReader(code), DocId::new("test".to_string()))
+            .parse()
+            .unwrap();
+        let parameters = parameters(&ast);
+
+        let names: Vec<_> = parameters.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            vec!["width", "height", "label", "half", "tall", "shape"],
+            names
+        );
+        assert_eq!(
+            Parameter {
+                name: "width".to_string(),
+                default: Some("10".to_string()),
+                kind: Some(Type::Number),
+                doc: Some("Width of the part\nin millimeters".to_string()),
+            },
+            parameters[0]
+        );
+        assert!(parameters[1].required());
+        assert_eq!(Some("Height of the part"), parameters[1].doc.as_deref());
+        assert_eq!(None, parameters[2].doc);
+        assert_eq!(Some(Type::Text), parameters[2].kind);
+        assert_eq!(Some("width / 2"), parameters[3].default.as_deref());
+        assert_eq!(Some(Type::Number), parameters[3].kind);
+        assert_eq!(Some(Type::Bool), parameters[4].kind);
+        assert_eq!(None, parameters[5].kind);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::project::Argument;
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ParamsFileError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("invalid parameters in {0}: {1}")]
+    Json(PathBuf, serde_json::Error),
+    #[error("invalid parameters in {0}: {1}")]
+    Toml(PathBuf, toml::de::Error),
+    #[error("unknown parameters file type {0}, expected .json or .toml")]
+    UnknownFormat(PathBuf),
+}
+
+/// Read a `--params` file, a JSON object or TOML table from parameter names to numbers,
+/// booleans, text or lists of them.
+pub fn read(path: &Path) -> Result<IndexMap<String, Argument>, ParamsFileError> {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    match extension {
+        Some("json") => from_json(&std::fs::read_to_string(path)?)
+            .map_err(|e| ParamsFileError::Json(path.to_path_buf(), e)),
+        Some("toml") => from_toml(&std::fs::read_to_string(path)?)
+            .map_err(|e| ParamsFileError::Toml(path.to_path_buf(), e)),
+        _ => Err(ParamsFileError::UnknownFormat(path.to_path_buf())),
+    }
+}
+
+pub fn from_json(params: &str) -> Result<IndexMap<String, Argument>, serde_json::Error> {
+    serde_json::from_str(params)
+}
+
+pub fn from_toml(params: &str) -> Result<IndexMap<String, Argument>, toml::de::Error> {
+    toml::from_str(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Literal;
+
+    #[test]
+    fn it_reads_json_and_toml() {
+        let json = from_json(
+            r#"{"width": 10, "hollow": true, "label": "a \"b\"", "holes": [[1, 2], [3.5, 4]]}"#,
+        )
+        .unwrap();
+        let toml =
+            from_toml("width = 10\nhollow = true\nlabel = 'a \"b\"'\nholes = [[1, 2], [3.5, 4]]")
+                .unwrap();
+        assert_eq!(json, toml);
+
+        let names: Vec<_> = json.keys().map(String::as_str).collect();
+        assert_eq!(vec!["width", "hollow", "label", "holes"], names);
+        assert_eq!(Argument::Number(10.), json["width"]);
+        assert_eq!("\"a \\\"b\\\"\"", json["label"].to_string());
+        assert_eq!("[[1, 2], [3.5, 4]]", json["holes"].to_string());
+        assert!(matches!(
+            json["holes"].to_literal(),
+            Literal::List(items) if items.len() == 2
+        ));
+
+        assert!(from_json(r#"{"width": null}"#).is_err());
+        assert!(matches!(
+            read(Path::new("params.yaml")),
+            Err(ParamsFileError::UnknownFormat(_))
+        ));
+    }
+}
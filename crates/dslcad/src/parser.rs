@@ -1,3 +1,4 @@
+mod dependencies;
 mod lexer;
 mod parse_error;
 mod reader;
@@ -6,27 +7,40 @@ mod syntax_tree;
 mod syntax_visitor;
 
 use lexer::{Lexer, Token};
-use logos::Logos;
+use logos::{Logos, Span};
 
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::ffi::OsStr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use crate::library::Library;
 use crate::parser::span_builder::SpanBuilder;
 use crate::parser::Literal::Resource;
 use crate::resources::ResourceLoader;
-pub use parse_error::{DocumentParseError, ParseError};
+use crate::suggestion::did_you_mean;
+pub use dependencies::Dependencies;
+use dslcad_storage::threemf::Unit;
+pub use parse_error::{DocumentParseError, ParseError, ParseErrors};
 pub use reader::Reader;
 pub use syntax_tree::*;
 pub use syntax_visitor::*;
 
+/// Allowance for float error when deciding whether the last step of a range reaches its end.
+const RANGE_TOLERANCE: f64 = 1e-9;
+
+/// Values of a swept parameter, each with a label to name outputs after.
+pub type SweepValues = Vec<(String, Expression)>;
+
 pub struct Parser<R> {
     reader: R,
     current_id: DocId,
     variables: HashSet<String>,
     to_parse: Vec<DocId>,
+    dependencies: HashSet<DocId>,
+    errors: Vec<ParseError>,
+    unit: Unit,
+    library_paths: Vec<PathBuf>,
     resource_loaders: HashMap<&'static str, Box<dyn ResourceLoader<R>>>,
 }
 
@@ -55,47 +69,191 @@ impl<T> Parser<T> {
             current_id: root,
             variables: HashSet::new(),
             to_parse: Vec::new(),
+            dependencies: HashSet::new(),
+            errors: Vec::new(),
+            unit: Unit::default(),
+            library_paths: Vec::new(),
             resource_loaders: HashMap::new(),
         }
     }
 
+    /// Unit that lengths are converted to when a number has a unit suffix such as `2in`.
+    pub fn with_unit(mut self, unit: Unit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Directories searched, in order, for paths such as `<std>/fasteners/m3`.
+    pub fn with_library_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.library_paths = paths;
+        self
+    }
+
+    /// Value of a number token, converting lengths to the model unit and angles to degrees.
+    fn number(&self, token: &str) -> f64 {
+        let split = token
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap_or(token.len());
+        let (number, suffix) = token.split_at(split);
+        let value = f64::from_str(number).unwrap();
+        match suffix {
+            "" | "deg" => value,
+            "rad" => value.to_degrees(),
+            length => {
+                let unit = Unit::from_str(length).expect("lexer only accepts known units");
+                value * unit.millimeters() / self.unit.millimeters()
+            }
+        }
+    }
+
+    /// Value of a number in a range such as `-1..3`, which may have a leading minus.
+    fn parse_range_number(&self, text: &str, span: &Span) -> Result<f64, DocumentParseError> {
+        let text = text.trim();
+        let (sign, number) = match text.strip_prefix('-') {
+            Some(number) => (-1., number.trim_start()),
+            None => (1., text),
+        };
+
+        let mut lexer = Token::lexer(number);
+        match lexer.next() {
+            Some(Token::Number) if lexer.remainder().is_empty() => {
+                Ok(sign * self.number(lexer.slice()))
+            }
+            _ => Err(DocumentParseError::Expected(
+                "number",
+                text.to_string(),
+                span.clone(),
+            )),
+        }
+    }
+
+    /// Values from `start..end` or `start..end step size`, including the end when a step lands
+    /// on it. Ranges are split by hand as `1..3` would lex as the numbers `1.` and `.3`.
+    fn parse_range(&self, range: &str, span: Span) -> Result<Vec<f64>, DocumentParseError> {
+        let (start, rest) = range.split_once("..").unwrap();
+        let (end, step) = match rest.split_once("step") {
+            Some((end, step)) => (end, Some(step)),
+            None => (rest, None),
+        };
+
+        let start = self.parse_range_number(start, &span)?;
+        let end = self.parse_range_number(end, &span)?;
+        let step = step
+            .map(|step| self.parse_range_number(step, &span))
+            .transpose()?
+            .unwrap_or(1.);
+
+        if end < start || step <= 0. {
+            return Err(DocumentParseError::InvalidRange(range.to_string(), span));
+        }
+        let count = ((end - start) / step + RANGE_TOLERANCE).floor() as usize + 1;
+        Ok((0..count).map(|i| start + i as f64 * step).collect())
+    }
+}
+
+impl<R: Reader> Parser<R> {
+    /// Parse arguments of the form `name=value`, where the value is an expression that may use
+    /// builtins such as `point(x=1)` but not documents or files.
     pub fn parse_arguments<'a>(
-        self,
+        mut self,
         arguments: impl Iterator<Item = &'a str>,
-    ) -> Result<HashMap<&'a str, Literal>, DocumentParseError> {
+    ) -> Result<HashMap<&'a str, Expression>, DocumentParseError> {
         let mut ret = HashMap::new();
         for argument in arguments {
-            let mut lexer = Token::lexer(argument);
+            // expressions look ahead for the token that ends them
+            let source = format!("{argument};");
+            let mut lexer = Token::lexer(&source);
 
-            let name = take!(self, lexer, Token::Identifier = "identifier" => lexer.slice());
+            take!(self, lexer, Token::Identifier = "identifier");
+            let name = &argument[lexer.span()];
             take!(self, lexer, Token::Equal = "=");
-            let value = take!(self, lexer,
-                Token::Minus = "-" => {
-                    let number = take!(self, lexer, Token::Number = "number" => lexer.slice());
-                    let value = f64::from_str(number).unwrap();
-                    Literal::Number(-value)
-                },
-                Token::Number = "number" => {
-                    let value = f64::from_str(lexer.slice()).unwrap();
-                    Literal::Number(value)
-                },
-                Token::Bool = "boolean" => {
-                    let value = lexer.slice() == "true";
-                    Literal::Bool(value)
-                },
-                Token::String = "string" => {
-                    let value = lexer.slice();
-                    Literal::Text(escape_string(value))
+            ret.insert(name, self.parse_argument_value(&mut lexer)?);
+            self.end_of_argument(&mut lexer)?;
+        }
+        Ok(ret)
+    }
+
+    /// Parse arguments that may take several values, either alternatives such as `size=10|20` or
+    /// a range such as `wall=1..3 step 0.5`. Each alternative is parsed like a value of
+    /// [Parser::parse_arguments], so `points=[1,2,3]` is a single list. Parameters keep the order
+    /// they were given in and every value has a label to name outputs after.
+    pub fn parse_sweep<'a>(
+        mut self,
+        arguments: impl Iterator<Item = &'a str>,
+    ) -> Result<Vec<(&'a str, SweepValues)>, DocumentParseError> {
+        let mut ret = Vec::new();
+        for argument in arguments {
+            let source = format!("{argument};");
+            let mut lexer = Token::lexer(&source);
+
+            take!(self, lexer, Token::Identifier = "identifier");
+            let name = &argument[lexer.span()];
+            take!(self, lexer, Token::Equal = "=");
+            let values = match lexer.clone().next() {
+                Some(Token::Number | Token::Minus) if lexer.remainder().contains("..") => {
+                    let start = lexer.span().end;
+                    let span = start..argument.len();
+                    let values = self.parse_range(&argument[start..], span.clone())?;
+                    lexer.bump(argument.len() - start);
+                    values
+                        .into_iter()
+                        .map(|value| {
+                            let literal = Expression::Literal(Literal::Number(value), span.clone());
+                            (value.to_string(), literal)
+                        })
+                        .collect()
                 }
-            );
-            ret.insert(name, value);
+                _ => {
+                    let mut values = Vec::new();
+                    loop {
+                        let start = lexer.span().end;
+                        let value = self.parse_argument_value(&mut lexer)?;
+                        values.push((
+                            argument_label(&value, &argument[start..lexer.span().end]),
+                            value,
+                        ));
+                        match lexer.clone().next() {
+                            Some(Token::Bar) => lexer.next(),
+                            _ => break,
+                        };
+                    }
+                    values
+                }
+            };
+            self.end_of_argument(&mut lexer)?;
+            ret.push((name, values));
         }
         Ok(ret)
     }
-}
 
-impl<R: Reader> Parser<R> {
-    pub fn parse(mut self) -> Result<Ast, ParseError> {
+    fn parse_argument_value(
+        &mut self,
+        lexer: &mut Lexer,
+    ) -> Result<Expression, DocumentParseError> {
+        let value = self.parse_expression(lexer)?;
+        if !self.dependencies.is_empty() {
+            return Err(DocumentParseError::DocumentInArgument(value.span().clone()));
+        }
+        Ok(value)
+    }
+
+    /// Check that nothing follows the value of an argument.
+    fn end_of_argument(&self, lexer: &mut Lexer) -> Result<(), DocumentParseError> {
+        take!(self, lexer, Token::Semicolon = "end of argument");
+        match lexer.next() {
+            Some(_) => Err(DocumentParseError::Expected(
+                "end of argument",
+                lexer.slice().to_string(),
+                lexer.span(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Parse the root document and every document it depends on.
+    ///
+    /// Parsing recovers at the end of a failing statement so every error is reported at once.
+    pub fn parse(mut self) -> Result<Ast, ParseErrors> {
         self.to_parse.push(self.current_id.clone());
         let mut ast = Ast::new(self.current_id.clone());
 
@@ -106,18 +264,26 @@ impl<R: Reader> Parser<R> {
 
             self.current_id = doc.clone();
             self.variables.clear();
-            let source = self
-                .reader
-                .read(doc.to_path())
-                .map_err(|_| DocumentParseError::NoSuchFile().with_source(doc.clone()))?;
+            self.dependencies.clear();
+            let Ok(source) = self.reader.read(doc.to_path()) else {
+                self.errors
+                    .push(DocumentParseError::NoSuchFile().with_source(doc.clone()));
+                ast.insert_document(doc, String::new(), Vec::new(), HashSet::new());
+                continue;
+            };
             let mut lexer = Token::lexer(&source);
-            let document = self
-                .parse_document(&mut lexer, None, true)
-                .map_err(|e| e.with_source(doc.clone()))?;
-            ast.documents.insert(doc, document);
+            let document = self.parse_document(&mut lexer, None, true);
+            let dependencies = std::mem::take(&mut self.dependencies);
+            ast.insert_document(doc, source, document, dependencies);
         }
 
-        Ok(ast)
+        if self.errors.is_empty() {
+            Ok(ast)
+        } else {
+            Err(ParseErrors {
+                errors: self.errors,
+            })
+        }
     }
 
     pub fn with_loader(
@@ -134,18 +300,43 @@ impl<R: Reader> Parser<R> {
         lexer: &mut Lexer,
         terminal: Option<Token>,
         allow_parameters: bool,
-    ) -> Result<Vec<Statement>, DocumentParseError> {
+    ) -> Vec<Statement> {
         let mut statements = Vec::new();
         while let Some(n) = lexer.clone().next() {
-            if Some(n) == terminal {
+            if Some(&n) == terminal.as_ref() {
                 break;
             }
 
-            let statement = self.parse_statement(lexer, allow_parameters)?;
-            statements.push(statement);
+            let start = lexer.clone();
+            match self.parse_statement(lexer, allow_parameters) {
+                Ok(statement) => statements.push(statement),
+                Err(error) => {
+                    self.errors.push(error.with_source(self.current_id.clone()));
+                    *lexer = start;
+                    Self::skip_statement(lexer, terminal.as_ref());
+                }
+            }
         }
 
-        Ok(statements)
+        statements
+    }
+
+    /// Skip past the `;` ending the current statement, stopping early before `terminal`.
+    fn skip_statement(lexer: &mut Lexer, terminal: Option<&Token>) {
+        let mut depth = 0;
+        while let Some(token) = lexer.clone().next() {
+            if depth == 0 && Some(&token) == terminal {
+                return;
+            }
+
+            lexer.next();
+            match token {
+                Token::OpenScope => depth += 1,
+                Token::CloseScope if depth > 0 => depth -= 1,
+                Token::Semicolon if depth == 0 => return,
+                _ => {}
+            }
+        }
     }
 
     fn parse_statement(
@@ -156,6 +347,8 @@ impl<R: Reader> Parser<R> {
         let mut peek = lexer.clone();
         match peek.next() {
             Some(Token::Var) => self.parse_variable_statement(lexer, allow_parameters),
+            Some(Token::Assert) => self.parse_assert_statement(lexer),
+            Some(Token::Import) => self.parse_import_statement(lexer),
             Some(_) => self.parse_return_statement(lexer),
             None => Err(DocumentParseError::UnexpectedEndOfFile()),
         }
@@ -171,6 +364,99 @@ impl<R: Reader> Parser<R> {
         Ok(Statement::CreatePart(expr, sb.to(lexer)))
     }
 
+    fn parse_assert_statement(
+        &mut self,
+        lexer: &mut Lexer,
+    ) -> Result<Statement, DocumentParseError> {
+        take!(self, lexer, Token::Assert = "assert");
+        // the lexer still points at the keyword so the span includes it
+        let sb = SpanBuilder::from(lexer);
+        let condition = self.parse_expression(lexer)?;
+        let message = take!(self, lexer,
+            Token::Semicolon = ";" => None,
+            Token::Comma = "," => {
+                let message = self.parse_expression(lexer)?;
+                take!(self, lexer, Token::Semicolon = "semicolon");
+                Some(message)
+            }
+        );
+        Ok(Statement::Assert(
+            Assert { condition, message },
+            sb.to(lexer),
+        ))
+    }
+
+    fn parse_import_statement(
+        &mut self,
+        lexer: &mut Lexer,
+    ) -> Result<Statement, DocumentParseError> {
+        take!(self, lexer, Token::Import = "import");
+        let sb = SpanBuilder::from(lexer);
+        let path = take!(self, lexer, Token::String = "string" => escape_string(lexer.slice()));
+        let path = self.resolve_path(&path, lexer.span())?;
+        take!(self, lexer, Token::As = "as");
+        let name = take!(self, lexer, Token::Identifier = "identifier" => lexer.slice());
+
+        if !self.variables.insert(name.to_string()) {
+            return Err(DocumentParseError::DuplicateVariableName(
+                name.to_string(),
+                lexer.span(),
+            ));
+        }
+        take!(self, lexer, Token::Semicolon = "semicolon");
+
+        let document = self.add_document(&path);
+        Ok(Statement::Import(
+            Import {
+                document,
+                name: name.to_string(),
+            },
+            sb.to(lexer),
+        ))
+    }
+
+    /// Path of `path` relative to the document being parsed, or for paths such as
+    /// `<std>/fasteners/m3` the first match in the library path.
+    fn resolve_path(&self, path: &str, span: Span) -> Result<PathBuf, DocumentParseError> {
+        let Some((library, rest)) = path
+            .strip_prefix('<')
+            .and_then(|path| path.split_once(">/"))
+        else {
+            return Ok(self
+                .current_id
+                .to_path()
+                .parent()
+                .unwrap_or(Path::new(""))
+                .join(path));
+        };
+
+        self.library_paths
+            .iter()
+            .map(|root| root.join(library).join(rest))
+            .find(|candidate| {
+                let file = match candidate.extension() {
+                    Some(_) => candidate.clone(),
+                    None => candidate.with_extension("ds"),
+                };
+                self.reader.exists(&file)
+            })
+            .ok_or_else(|| DocumentParseError::NotInLibraryPath(path.to_string(), span))
+    }
+
+    /// Queue the document at `path` for parsing and record it as a dependency.
+    fn add_document(&mut self, path: &Path) -> DocId {
+        let id = DocId::new(
+            self.reader
+                .normalize(&path.with_extension("ds"))
+                .to_str()
+                .unwrap()
+                .to_string(),
+        );
+        self.to_parse.push(id.clone());
+        self.dependencies.insert(id.clone());
+        id
+    }
+
     fn parse_variable_statement(
         &mut self,
         lexer: &mut Lexer,
@@ -214,34 +500,44 @@ impl<R: Reader> Parser<R> {
     }
 
     fn parse_call(&mut self, lexer: &mut Lexer) -> Result<Expression, DocumentParseError> {
-        let path = take!(self, lexer,
-            Token::Path = "path" => {
-                let path =  lexer.slice();
-
-                let mut buf = std::path::PathBuf::new();
-                buf.push(self.current_id.to_path());
-                let buf = buf.parent().unwrap();
-                let buf = buf.join(path);
-
-                match buf.extension().unwrap_or(OsStr::new("ds")).to_str().unwrap() {
-                    "ds" => {
-                        let id = DocId::new(self.reader.normalize(Path::new(&buf.with_extension("ds"))).to_str().unwrap().to_string());
-                        self.to_parse.push(id.clone());
-                        CallPath::Document(id)
-                    },
-                    extension => {
-                        if let Some(loader) = self.resource_loaders.get(extension) {
-                            take!(self, lexer, Token::OpenBracket = "(");
-                            take!(self, lexer, Token::CloseBracket = ")");
-
-                            return Ok(Expression::Literal(Resource(loader.load(buf.to_str().unwrap(), &self.reader)?), lexer.span()))
-                        } else {
-                            return Err(DocumentParseError::UnknownResourceType(extension.to_owned(), lexer.span()))
-                        }
-                    }
+        take!(self, lexer,
+            Token::Path = "path" => {},
+            Token::Less = "library path" => {
+                // `<` is also the less than operator so library paths are lexed here instead
+                match library_path_length(lexer.remainder()) {
+                    Some(length) => lexer.bump(length),
+                    None => return Err(DocumentParseError::Expected("library path", lexer.slice().to_string(), lexer.span())),
                 }
             }
         );
+
+        let path_span = lexer.span();
+        let buf = self.resolve_path(lexer.slice(), path_span.clone())?;
+        let path = match buf
+            .extension()
+            .unwrap_or(OsStr::new("ds"))
+            .to_str()
+            .unwrap()
+        {
+            "ds" => CallPath::Document(self.add_document(&buf)),
+            extension => {
+                if let Some(loader) = self.resource_loaders.get(extension) {
+                    take!(self, lexer, Token::OpenBracket = "(");
+                    take!(self, lexer, Token::CloseBracket = ")");
+
+                    let path = buf.to_str().unwrap();
+                    let resource = loader.load(path, &self.reader).map_err(|reason| {
+                        DocumentParseError::InvalidResource(path.to_owned(), reason, path_span)
+                    })?;
+                    return Ok(Expression::Literal(Resource(resource.into()), lexer.span()));
+                } else {
+                    return Err(DocumentParseError::UnknownResourceType(
+                        extension.to_owned(),
+                        lexer.span(),
+                    ));
+                }
+            }
+        };
         let sb = SpanBuilder::from(lexer);
 
         let args = self.parse_call_arguments(lexer)?;
@@ -271,9 +567,9 @@ impl<R: Reader> Parser<R> {
                 },
                 _ = "expression" => {
 
-                    let mut arg_lexer = lexer.clone();
-                    if let Ok((name, expression)) = self.parse_argument(&mut arg_lexer) {
-                        *lexer = arg_lexer;
+                    let mut named = lexer.clone();
+                    if let (Some(Token::Identifier), Some(Token::Equal)) = (named.next(), named.next()) {
+                        let (name, expression) = self.parse_argument(lexer)?;
                         args.push_back(Argument::Named(name, Box::new(expression)));
                     } else {
                         let mut expr_lexer = lexer.clone();
@@ -306,9 +602,13 @@ impl<R: Reader> Parser<R> {
         let name = take!(self, lexer, Token::Identifier = "identifier" => lexer.slice());
         let sb = SpanBuilder::from(lexer);
 
-        if !self.variables.contains(name) && !Library::default().contains(name) {
+        let library = Library::default();
+        if !self.variables.contains(name) && !library.contains(name) {
+            let mut candidates: Vec<&str> = library.names().collect();
+            candidates.extend(self.variables.iter().map(|v| v.as_str()));
             return Err(DocumentParseError::UndeclaredIdentifier(
                 name.to_string(),
+                did_you_mean(name, candidates),
                 lexer.span(),
             ));
         }
@@ -440,7 +740,7 @@ impl<R: Reader> Parser<R> {
         parse_right: impl Fn(&mut Self, &mut Lexer) -> Result<Expression, DocumentParseError>,
     ) -> Result<Expression, DocumentParseError> {
         lexer.next();
-        let sb = SpanBuilder::from(lexer);
+        let start = left.span().start;
         let right = parse_right(self, lexer)?;
         Ok(Expression::Invocation(
             Invocation {
@@ -459,7 +759,7 @@ impl<R: Reader> Parser<R> {
                 ]
                 .into(),
             },
-            sb.to(lexer),
+            start..lexer.span().end,
         ))
     }
 
@@ -471,7 +771,7 @@ impl<R: Reader> Parser<R> {
         take!(self, lexer, Token::OpenScope = "{");
 
         let outer = self.variables.clone();
-        let statements = self.parse_document(lexer, Some(Token::CloseScope), allow_parameters)?;
+        let statements = self.parse_document(lexer, Some(Token::CloseScope), allow_parameters);
         self.variables = outer;
 
         take!(self, lexer, Token::CloseScope = "}");
@@ -645,13 +945,14 @@ impl<R: Reader> Parser<R> {
             let mut peek = lexer.clone();
             first = match peek.next() {
                 Some(Token::OpenBracket) => {
+                    let start = first.span().start;
                     let arguments = self.parse_call_arguments(lexer)?;
                     Expression::Invocation(
                         Invocation {
                             path: CallPath::Function(first.into()),
                             arguments,
                         },
-                        Default::default(),
+                        start..lexer.span().end,
                     )
                 }
                 Some(Token::Period) => {
@@ -698,6 +999,9 @@ impl<R: Reader> Parser<R> {
                 let sb = SpanBuilder::from(lexer);
                 let expr = self.parse_terminal_expression(lexer)?;
                 let span = sb.to(lexer);
+                if let Expression::Literal(Literal::Number(value), _) = expr {
+                    return Ok(Expression::Literal(Literal::Number(-value), span));
+                }
                 Expression::Invocation( Invocation {
                     path: CallPath::Function(Expression::Reference( Reference { name: "subtract".to_string() } , 0..0).into()),
                     arguments: VecDeque::from([
@@ -723,7 +1027,7 @@ impl<R: Reader> Parser<R> {
             },
             Token::Number = "number" => {
                 lexer.next();
-                let value = f64::from_str(lexer.slice()).unwrap();
+                let value = self.number(lexer.slice());
                 Expression::Literal(Literal::Number(value), lexer.span())
             },
             Token::Bool = "boolean" => {
@@ -737,6 +1041,7 @@ impl<R: Reader> Parser<R> {
                 Expression::Literal(Literal::Text(escape_string(value)), lexer.span())
             },
             Token::Path = "path" => self.parse_call(lexer)?,
+            Token::Less = "library path" => self.parse_call(lexer)?,
             Token::Identifier = "identifier" => self.parse_reference(lexer)?,
             Token::OpenBracket = "(" => {
                 lexer.next();
@@ -770,14 +1075,73 @@ impl<R: Reader> Parser<R> {
     }
 }
 
+/// Length of the `std>/fasteners/m3` that follows the `<` of a library path.
+fn library_path_length(text: &str) -> Option<usize> {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let library = text.find(|c| !is_name(c))?;
+    if library == 0 || !text[library..].starts_with(">/") {
+        return None;
+    }
+
+    let mut length = library + 1;
+    while let Some(rest) = text[length..].strip_prefix('/') {
+        let segment = rest
+            .find(|c: char| !is_name(c) && c != '.')
+            .unwrap_or(rest.len());
+        if segment == 0 {
+            break;
+        }
+        length += 1 + segment;
+    }
+    Some(length)
+}
+
+/// Text to name outputs after for a swept value, the value itself for numbers, booleans and text
+/// or its `source` for anything else.
+fn argument_label(value: &Expression, source: &str) -> String {
+    match value {
+        Expression::Literal(Literal::Number(value), _) => value.to_string(),
+        Expression::Literal(Literal::Bool(value), _) => value.to_string(),
+        Expression::Literal(Literal::Text(value), _) => value.clone(),
+        _ => source.trim().to_string(),
+    }
+}
+
+/// Value of a string literal, unknown escapes such as `\d` are kept as they are written.
 fn escape_string(input: &str) -> String {
-    let source = input[1..input.len() - 1].to_string();
-    source
-        .replace(r"\r", "\r")
-        .replace(r"\n", "\n")
-        .replace(r"\t", "\t")
-        .replace("\\\"", "\"")
-        .replace(r"\\", r"\")
+    let mut value = String::new();
+    let mut chars = input[1..input.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('r') => value.push('\r'),
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some(c @ ('"' | '\\')) => value.push(c),
+            Some(c) => value.extend(['\\', c]),
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+/// String literal that `escape_string` reads back as `value`.
+pub fn quote_string(value: &str) -> String {
+    let mut literal = String::from('"');
+    for c in value.chars() {
+        match c {
+            '\r' => literal.push_str(r"\r"),
+            '\n' => literal.push_str(r"\n"),
+            '\t' => literal.push_str(r"\t"),
+            '"' | '\\' => literal.extend(['\\', c]),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
 }
 
 #[cfg(test)]
@@ -796,7 +1160,10 @@ pub mod tests {
     struct TestRes;
 
     impl<R: Reader> ResourceLoader<R> for TestRes {
-        fn load(&self, _: &str, _: &R) -> Result<Box<dyn Resource>, DocumentParseError> {
+        fn load(&self, path: &str, _: &R) -> Result<Box<dyn Resource>, String> {
+            if path.contains("broken") {
+                return Err("corrupt file".to_string());
+            }
             Ok(Box::new(self.clone()))
         }
     }
@@ -807,7 +1174,7 @@ pub mod tests {
         }
     }
 
-    fn parse(code: &'static str, action: impl for<'a> FnOnce(Result<Ast, ParseError>)) {
+    fn parse(code: &'static str, action: impl for<'a> FnOnce(Result<Ast, ParseErrors>)) {
         let res = Parser::new(TestReader(code), DocId::new("test".to_string()))
             .with_loader("stl", TestRes)
             .parse();
@@ -829,10 +1196,132 @@ pub mod tests {
             .parse_arguments(vec!["a=5", "b=true", "c=\"hi\"", "d=-5"].into_iter())
             .unwrap();
 
-        assert!(matches!(parsed.get("a").unwrap(), Literal::Number(_)));
-        assert!(matches!(parsed.get("b").unwrap(), Literal::Bool(true)));
-        assert!(matches!(parsed.get("c").unwrap(), Literal::Text(_)));
-        assert!(matches!(parsed.get("d").unwrap(), Literal::Number(_)));
+        assert!(matches!(
+            parsed["a"],
+            Expression::Literal(Literal::Number(_), _)
+        ));
+        assert!(matches!(
+            parsed["b"],
+            Expression::Literal(Literal::Bool(true), _)
+        ));
+        assert!(matches!(
+            parsed["c"],
+            Expression::Literal(Literal::Text(_), _)
+        ));
+        assert!(matches!(parsed["d"], Expression::Literal(Literal::Number(n), _) if n == -5.));
+    }
+
+    #[test]
+    fn it_parses_argument_expressions() {
+        let p = Parser::new((), DocId::new(String::new()));
+        let parsed = p
+            .parse_arguments(
+                vec!["pts=[1,2,3]", "origin=point(x=1)", "x=2*3", "y=-(1 + 2)"].into_iter(),
+            )
+            .unwrap();
+
+        assert!(
+            matches!(&parsed["pts"], Expression::Literal(Literal::List(items), _) if items.len() == 3)
+        );
+        assert!(matches!(parsed["origin"], Expression::Invocation(_, _)));
+        assert!(matches!(parsed["x"], Expression::Invocation(_, _)));
+        assert!(matches!(parsed["y"], Expression::Invocation(_, _)));
+
+        let arguments = |argument: &'static str| {
+            Parser::new((), DocId::new(String::new())).parse_arguments(vec![argument].into_iter())
+        };
+        assert!(matches!(
+            arguments("a=./part()"),
+            Err(DocumentParseError::DocumentInArgument(_))
+        ));
+        assert!(matches!(
+            arguments("a=width"),
+            Err(DocumentParseError::UndeclaredIdentifier(_, _, _))
+        ));
+        assert!(matches!(
+            arguments("a=1 2"),
+            Err(DocumentParseError::Expected("end of argument", _, _))
+        ));
+        assert!(matches!(
+            arguments("a=1;2"),
+            Err(DocumentParseError::Expected("end of argument", _, _))
+        ));
+    }
+
+    #[test]
+    fn it_parses_sweeps() {
+        let numbers = |values: &[(String, Expression)]| -> Vec<f64> {
+            values
+                .iter()
+                .map(|(_, value)| match value {
+                    Expression::Literal(Literal::Number(value), _) => *value,
+                    _ => panic!("expected a number"),
+                })
+                .collect()
+        };
+        let labels = |values: &[(String, Expression)]| -> Vec<String> {
+            values.iter().map(|(label, _)| label.clone()).collect()
+        };
+
+        let p = Parser::new((), DocId::new(String::new()));
+        let parsed = p
+            .parse_sweep(
+                vec![
+                    "a=10|20|-30",
+                    "b=1..3 step 0.5",
+                    "c=1..3",
+                    "d=\"x..y\"",
+                    "e=[1, 2] | point(x=1)",
+                    "f=[1, 2, 3]",
+                ]
+                .into_iter(),
+            )
+            .unwrap();
+        let names: Vec<_> = parsed.iter().map(|(name, _)| *name).collect();
+        assert_eq!(vec!["a", "b", "c", "d", "e", "f"], names);
+        assert_eq!(vec![10., 20., -30.], numbers(&parsed[0].1));
+        assert_eq!(vec!["10", "20", "-30"], labels(&parsed[0].1));
+        assert_eq!(vec![1., 1.5, 2., 2.5, 3.], numbers(&parsed[1].1));
+        assert_eq!(vec!["1", "1.5", "2", "2.5", "3"], labels(&parsed[1].1));
+        assert_eq!(vec![1., 2., 3.], numbers(&parsed[2].1));
+        assert!(
+            matches!(&parsed[3].1[..], [(_, Expression::Literal(Literal::Text(text), _))] if text == "x..y")
+        );
+        assert_eq!(vec!["x..y"], labels(&parsed[3].1));
+        assert_eq!(vec!["[1, 2]", "point(x=1)"], labels(&parsed[4].1));
+        assert!(matches!(
+            &parsed[5].1[..],
+            [(_, Expression::Literal(Literal::List(items), _))] if items.len() == 3
+        ));
+
+        let p = Parser::new((), DocId::new(String::new())).with_unit(Unit::Inch);
+        let parsed = p
+            .parse_sweep(vec!["a=0..2in step 25.4mm"].into_iter())
+            .unwrap();
+        assert_eq!(vec![0., 1., 2.], numbers(&parsed[0].1));
+
+        let p = Parser::new((), DocId::new(String::new()));
+        assert!(matches!(
+            p.parse_sweep(vec!["a=3..1"].into_iter()),
+            Err(DocumentParseError::InvalidRange(_, _))
+        ));
+    }
+
+    #[test]
+    fn it_converts_units() {
+        let p = Parser::new((), DocId::new(String::new())).with_unit(Unit::Inch);
+        let parsed = p
+            .parse_arguments(vec!["a=25.4mm", "b=-2in", "c=3", "d=1rad"].into_iter())
+            .unwrap();
+        let number = |name: &str| match parsed[name] {
+            Expression::Literal(Literal::Number(n), _) => n,
+            _ => panic!("expected a number"),
+        };
+
+        assert_eq!(1., number("a"));
+        assert_eq!(-2., number("b"));
+        assert_eq!(3., number("c"));
+        assert!((number("d") - 57.2958).abs() < 0.001);
     }
 
     #[test]
@@ -843,6 +1332,20 @@ pub mod tests {
         assert_eq!("te\rst", escape_string("\"te\\rst\""));
         assert_eq!("te\\st", escape_string("\"te\\\\st\""));
         assert_eq!("te\"st", escape_string("\"te\\\"st\""));
+        assert_eq!(r"C:\new", escape_string(r#""C:\\new""#));
+        assert_eq!(r"\d+", escape_string(r#""\d+""#));
+
+        for value in [r"C:\new\", "a \"b\"\n\tc\r"] {
+            assert_eq!(value, escape_string(&quote_string(value)));
+        }
+    }
+
+    #[test]
+    fn it_measures_library_paths() {
+        assert_eq!(Some(17), library_path_length("std>/fasteners/m3(size=2);"));
+        assert_eq!(Some(13), library_path_length("std>/logo.stl()"));
+        assert_eq!(None, library_path_length("std > 2;"));
+        assert_eq!(None, library_path_length("1;"));
     }
 
     #[test]
@@ -868,6 +1371,63 @@ pub mod tests {
         });
     }
 
+    #[test]
+    fn it_tracks_dependencies() {
+        parse(
+            "var size = 1; var c; var a = map ./part(size=size).x as y: y + c;",
+            |a| {
+                let ast = a.unwrap();
+                let dependencies: Vec<_> = ast.dependencies(ast.root()).collect();
+                assert_eq!(vec![&DocId::new("./part.ds".to_string())], dependencies);
+
+                let dependencies = Dependencies::of(&ast.root_document()[2]);
+                assert_eq!(
+                    vec!["a", "add", "c", "size", "y"],
+                    dependencies.references.iter().collect::<Vec<_>>()
+                );
+                assert_eq!(1, dependencies.documents.len());
+                assert!(!dependencies.resources);
+            },
+        );
+    }
+
+    #[test]
+    fn it_recovers_from_errors_at_statement_boundaries() {
+        parse("var a = ; var b = 2; cube(x=b;", |a| {
+            assert_eq!(2, a.unwrap_err().errors.len());
+        });
+        parse("var f = func { var = 1; 5; }; var g = f(;", |a| {
+            let errors = a.unwrap_err().errors;
+            assert_eq!(2, errors.len());
+            assert_eq!("expected identifier, found =", errors[0].to_string());
+        });
+    }
+
+    #[test]
+    fn it_declares_variables_whose_value_fails_to_parse() {
+        parse("var a = 1 +; var b = a * 2; cube(x=a);", |a| {
+            assert_eq!(1, a.unwrap_err().errors.len());
+        });
+        parse("var f = func { var a = [1, ; a; }; f();", |a| {
+            assert_eq!(1, a.unwrap_err().errors.len());
+        });
+    }
+
+    #[test]
+    fn it_suggests_similar_identifiers() {
+        parse("var size = 1; cube(x=sise);", |a| {
+            let errors = a.unwrap_err().errors;
+            assert_eq!(
+                "use of undeclared identifier sise, did you mean 'size'?",
+                errors[0].to_string()
+            );
+        });
+        parse("cueb();", |a| {
+            let errors = a.unwrap_err().errors;
+            assert!(errors[0].to_string().ends_with("did you mean 'cube'?"));
+        });
+    }
+
     #[test]
     fn it_can_parse_resource_calls() {
         parse("./cube.stl();", |a| {
@@ -875,6 +1435,18 @@ pub mod tests {
         });
     }
 
+    #[test]
+    fn it_points_invalid_resources_at_their_path() {
+        parse("2;\n./broken.stl();", |a| {
+            let errors = a.unwrap_err().errors;
+            assert!(matches!(
+                &errors[0].error,
+                DocumentParseError::InvalidResource(_, reason, span)
+                    if reason == "corrupt file" && *span == (3..15)
+            ));
+        });
+    }
+
     #[test]
     fn it_can_parse() {
         parse("cube(x=10,y=10);", |a| {
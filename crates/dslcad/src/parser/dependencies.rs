@@ -0,0 +1,146 @@
+use crate::parser::{Argument, DocId};
+use crate::parser::{
+    Assert, CallPath, Expression, ExpressionVisitor, If, Import, Index, Invocation, Literal,
+    LiteralVisitor, Map, NestedScope, Property, Reduce, Reference, Statement, StatementVisitor,
+    Variable,
+};
+use crate::resources::Resource;
+use logos::Span;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Everything outside of a statement that its value can depend on.
+///
+/// References are collected without regard for local bindings, so the set may contain names that
+/// are bound inside the statement itself.
+#[derive(Debug, Default)]
+pub struct Dependencies {
+    pub references: BTreeSet<String>,
+    pub documents: Vec<DocId>,
+    pub resources: bool,
+}
+
+impl Dependencies {
+    pub fn of(statement: &Statement) -> Self {
+        let mut dependencies = Self::default();
+        dependencies.visit_statement(statement);
+        dependencies
+            .documents
+            .sort_by(|a, b| a.to_str().cmp(b.to_str()));
+        dependencies.documents.dedup();
+        dependencies
+    }
+
+    fn visit_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+    }
+}
+
+impl StatementVisitor for Dependencies {
+    type Result = ();
+
+    fn visit_variable(&mut self, Variable { name, value }: &Variable, _span: &Span) {
+        // an argument passed by the caller overrides the default value
+        self.references.insert(name.to_string());
+        if let Some(value) = value {
+            self.visit_expression(value);
+        }
+    }
+
+    fn visit_create_part(&mut self, expr: &Expression, _span: &Span) {
+        self.visit_expression(expr);
+    }
+
+    fn visit_import(&mut self, Import { document, .. }: &Import, _span: &Span) {
+        self.documents.push(document.clone());
+    }
+
+    fn visit_assert(&mut self, Assert { condition, message }: &Assert, _span: &Span) {
+        self.visit_expression(condition);
+        if let Some(message) = message {
+            self.visit_expression(message);
+        }
+    }
+}
+
+impl ExpressionVisitor for Dependencies {
+    type Result = ();
+
+    fn visit_literal(&mut self, l: &Literal, _s: &Span) {
+        l.walk_literal(self);
+    }
+
+    fn visit_reference(&mut self, l: &Reference, _s: &Span) {
+        self.references.insert(l.name.to_string());
+    }
+
+    fn visit_invocation(&mut self, l: &Invocation, _s: &Span) {
+        match &l.path {
+            CallPath::Function(path) => self.visit_expression(path),
+            CallPath::Document(id) => self.documents.push(id.clone()),
+        }
+        for argument in &l.arguments {
+            match argument {
+                Argument::Named(_, expr) | Argument::Unnamed(expr) => self.visit_expression(expr),
+            }
+        }
+    }
+
+    fn visit_property(&mut self, l: &Property, _s: &Span) {
+        self.visit_expression(&l.target);
+    }
+
+    fn visit_index(&mut self, l: &Index, _s: &Span) {
+        self.visit_expression(&l.target);
+        self.visit_expression(&l.index);
+    }
+
+    fn visit_map(&mut self, l: &Map, _s: &Span) {
+        self.visit_expression(&l.range);
+        self.visit_expression(&l.action);
+    }
+
+    fn visit_reduce(&mut self, l: &Reduce, _s: &Span) {
+        if let Some(root) = &l.root {
+            self.visit_expression(root);
+        }
+        self.visit_expression(&l.range);
+        self.visit_expression(&l.action);
+    }
+
+    fn visit_if(&mut self, l: &If, _s: &Span) {
+        self.visit_expression(&l.condition);
+        self.visit_expression(&l.if_true);
+        self.visit_expression(&l.if_false);
+    }
+
+    fn visit_scope(&mut self, l: &NestedScope, _s: &Span) {
+        self.visit_statements(&l.statements);
+    }
+}
+
+impl LiteralVisitor for Dependencies {
+    type Result = ();
+
+    fn visit_number(&mut self, _v: &f64) {}
+
+    fn visit_bool(&mut self, _v: &bool) {}
+
+    fn visit_text(&mut self, _v: &str) {}
+
+    fn visit_list(&mut self, v: &[Expression]) {
+        for expr in v {
+            self.visit_expression(expr);
+        }
+    }
+
+    fn visit_resource(&mut self, _v: &dyn Resource) {
+        self.resources = true;
+    }
+
+    fn visit_function(&mut self, v: &Arc<Vec<Statement>>) {
+        self.visit_statements(v);
+    }
+}
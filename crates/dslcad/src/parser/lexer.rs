@@ -18,6 +18,8 @@ pub enum Token {
     Comma,
     #[token(":")]
     Colon,
+    #[token("|")]
+    Bar,
     #[token(";")]
     Semicolon,
     #[token(".")]
@@ -82,13 +84,17 @@ pub enum Token {
 
     #[regex(r"true|false")]
     Bool,
-    #[regex(r"\d+(\.\d*)?")]
+    #[regex(r"\d+(\.\d*)?(um|mm|cm|m|in|ft|deg|rad)?")]
     Number,
-    #[regex("\"((\\\\\")|[^\"])*\"")]
+    #[regex(r#""([^"\\]|\\.)*""#)]
     String,
 
     #[token("var")]
     Var,
+    #[token("assert")]
+    Assert,
+    #[token("import")]
+    Import,
 
     #[regex("[a-zA-Z_][a-zA-Z0-9_]*")]
     Identifier,
@@ -113,10 +119,17 @@ mod tests {
         assert_eq!(vec![Var, Identifier, Equal, Bool], tokens("var x = true"));
     }
 
+    #[test]
+    fn it_can_lex_units() {
+        assert_eq!(vec![Number, Multiply, Number], tokens("10mm * 2.5in"));
+        assert_eq!(vec![Number, Identifier], tokens("45deg x"));
+    }
+
     #[test]
     fn it_can_lex_strings() {
         assert_eq!(vec![String], tokens("\"test\""));
         assert_eq!(vec![String], tokens("\"te\\\"st\""));
+        assert_eq!(vec![String, Comma], tokens(r#""C:\dir\\","#));
     }
 
     #[test]
@@ -1,5 +1,6 @@
 use crate::parser::DocId;
 use crate::source::LineColExt;
+use crate::suggestion::suggestion_message;
 use logos::Span;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
@@ -19,28 +20,69 @@ impl Display for ParseError {
 
 impl Error for ParseError {}
 
+/// Every error found while parsing a script and the documents it depends on.
+#[derive(Debug)]
+pub struct ParseErrors {
+    pub errors: Vec<ParseError>,
+}
+
+impl Display for ParseErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {}", error.file, error.error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseErrors {}
+
+impl From<ParseError> for ParseErrors {
+    fn from(value: ParseError) -> Self {
+        ParseErrors {
+            errors: vec![value],
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum DocumentParseError {
     #[error("file not found")]
     NoSuchFile(),
     #[error("unexpected end of file")]
     UnexpectedEndOfFile(),
+    #[error("could not load {0}: {1}")]
+    InvalidResource(String, String, Span),
     #[error("unknown resource extension {0}")]
     UnknownResourceType(String, Span),
-    #[error("use of undeclared identifier {0}")]
-    UndeclaredIdentifier(String, Span),
+    #[error("could not find {0} in the library path")]
+    NotInLibraryPath(String, Span),
+    #[error(
+        "invalid range {0}, the end must not be before the start and the step must be positive"
+    )]
+    InvalidRange(String, Span),
+    #[error("arguments can only use builtins, not other documents")]
+    DocumentInArgument(Span),
+    #[error("use of undeclared identifier {0}{}", suggestion_message(.1))]
+    UndeclaredIdentifier(String, Option<String>, Span),
     #[error("a variable already exists with the name {0}")]
     DuplicateVariableName(String, Span),
     #[error("parameters are not allowed in scopes")]
     ParametersNotAllowedInScopes(Span),
-    #[error("expected {0} but found {1}")]
+    #[error("expected {0}, found {1}")]
     Expected(&'static str, String, Span),
-    #[error("expected one of {} but found {1}", one_of_list(.0))]
+    #[error("expected {}, found {1}", one_of_list(.0))]
     ExpectedOneOf(Vec<&'static str>, String, Span),
 }
 
 fn one_of_list(list: &[&'static str]) -> String {
-    list.join(" or ")
+    match list {
+        [single] => single.to_string(),
+        _ => format!("one of {}", list.join(" or ")),
+    }
 }
 
 impl DocumentParseError {
@@ -49,10 +91,14 @@ impl DocumentParseError {
             DocumentParseError::NoSuchFile() => (0, 0..0),
             DocumentParseError::UnexpectedEndOfFile() => {
                 let (i, line) = text.split('\n').enumerate().last().unwrap_or_default();
-                (i, line.len()..line.len())
+                (i + 1, line.len()..line.len())
             }
-            DocumentParseError::UnknownResourceType(_, span)
-            | DocumentParseError::UndeclaredIdentifier(_, span)
+            DocumentParseError::InvalidResource(_, _, span)
+            | DocumentParseError::UnknownResourceType(_, span)
+            | DocumentParseError::NotInLibraryPath(_, span)
+            | DocumentParseError::InvalidRange(_, span)
+            | DocumentParseError::DocumentInArgument(span)
+            | DocumentParseError::UndeclaredIdentifier(_, _, span)
             | DocumentParseError::DuplicateVariableName(_, span)
             | DocumentParseError::ParametersNotAllowedInScopes(span)
             | DocumentParseError::Expected(_, _, span)
@@ -60,6 +106,40 @@ impl DocumentParseError {
         }
     }
 
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            DocumentParseError::NoSuchFile() | DocumentParseError::UnexpectedEndOfFile() => None,
+            DocumentParseError::InvalidResource(_, _, span)
+            | DocumentParseError::UnknownResourceType(_, span)
+            | DocumentParseError::NotInLibraryPath(_, span)
+            | DocumentParseError::InvalidRange(_, span)
+            | DocumentParseError::DocumentInArgument(span)
+            | DocumentParseError::UndeclaredIdentifier(_, _, span)
+            | DocumentParseError::DuplicateVariableName(_, span)
+            | DocumentParseError::ParametersNotAllowedInScopes(span)
+            | DocumentParseError::Expected(_, _, span)
+            | DocumentParseError::ExpectedOneOf(_, _, span) => Some(span.clone()),
+        }
+    }
+
+    /// Stable identifier of the error for tools that consume `--message-format json`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DocumentParseError::NoSuchFile() => "NoSuchFile",
+            DocumentParseError::UnexpectedEndOfFile() => "UnexpectedEndOfFile",
+            DocumentParseError::InvalidResource(_, _, _) => "InvalidResource",
+            DocumentParseError::UnknownResourceType(_, _) => "UnknownResourceType",
+            DocumentParseError::NotInLibraryPath(_, _) => "NotInLibraryPath",
+            DocumentParseError::InvalidRange(_, _) => "InvalidRange",
+            DocumentParseError::DocumentInArgument(_) => "DocumentInArgument",
+            DocumentParseError::UndeclaredIdentifier(_, _, _) => "UndeclaredIdentifier",
+            DocumentParseError::DuplicateVariableName(_, _) => "DuplicateVariableName",
+            DocumentParseError::ParametersNotAllowedInScopes(_) => "ParametersNotAllowedInScopes",
+            DocumentParseError::Expected(_, _, _) => "Expected",
+            DocumentParseError::ExpectedOneOf(_, _, _) => "ExpectedOneOf",
+        }
+    }
+
     pub fn with_source(self, file: DocId) -> ParseError {
         ParseError { error: self, file }
     }
@@ -77,6 +157,6 @@ pub mod tests {
             .with_source(DocId::new("test.txt".to_string()));
 
         assert_eq!("v", source[range].to_string());
-        assert_eq!("expected foo but found test", format!("{error}"))
+        assert_eq!("expected foo, found test", format!("{error}"))
     }
 }
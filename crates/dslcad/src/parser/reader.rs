@@ -4,4 +4,23 @@ pub trait Reader {
     fn read_bytes(&self, path: &Path) -> Result<Vec<u8>, std::io::Error>;
     fn read(&self, path: &Path) -> Result<String, std::io::Error>;
     fn normalize(&self, path: &Path) -> PathBuf;
+
+    fn exists(&self, path: &Path) -> bool {
+        self.read_bytes(path).is_ok()
+    }
+}
+
+/// Reads no files, for parsing text such as arguments that can not refer to documents.
+impl Reader for () {
+    fn read_bytes(&self, _path: &Path) -> Result<Vec<u8>, std::io::Error> {
+        Err(std::io::ErrorKind::NotFound.into())
+    }
+
+    fn read(&self, _path: &Path) -> Result<String, std::io::Error> {
+        Err(std::io::ErrorKind::NotFound.into())
+    }
+
+    fn normalize(&self, path: &Path) -> PathBuf {
+        path.to_path_buf()
+    }
 }
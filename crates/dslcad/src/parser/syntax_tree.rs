@@ -1,11 +1,14 @@
+use crate::parser::dependencies::Dependencies;
 use crate::parser::syntax_visitor::{ExpressionVisitor, LiteralVisitor, StatementVisitor};
 use crate::resources::Resource;
 use logos::Span;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::path::Path;
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct DocId {
@@ -47,6 +50,8 @@ impl Display for DocId {
 pub struct Ast {
     root: DocId,
     pub documents: HashMap<DocId, Vec<Statement>>,
+    sources: HashMap<DocId, String>,
+    dependencies: HashMap<DocId, HashSet<DocId>>,
 }
 
 impl Ast {
@@ -54,9 +59,78 @@ impl Ast {
         Self {
             root,
             documents: HashMap::new(),
+            sources: HashMap::new(),
+            dependencies: HashMap::new(),
         }
     }
 
+    pub fn insert_document(
+        &mut self,
+        id: DocId,
+        source: String,
+        statements: Vec<Statement>,
+        dependencies: HashSet<DocId>,
+    ) {
+        self.sources.insert(id.clone(), source);
+        self.dependencies.insert(id.clone(), dependencies);
+        self.documents.insert(id, statements);
+    }
+
+    pub fn source(&self, id: &DocId) -> Option<&str> {
+        self.sources.get(id).map(|s| s.as_str())
+    }
+
+    /// Documents invoked directly from `id`.
+    pub fn dependencies(&self, id: &DocId) -> impl Iterator<Item = &DocId> {
+        self.dependencies.get(id).into_iter().flatten()
+    }
+
+    /// Hash of the source of `id` and every document it depends on, directly or not.
+    pub fn fingerprint(&self, id: &DocId) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash_document(id, &mut HashSet::new(), &mut hasher);
+        hasher.finish()
+    }
+
+    fn hash_document<'a>(
+        &'a self,
+        id: &'a DocId,
+        visited: &mut HashSet<&'a DocId>,
+        hasher: &mut impl Hasher,
+    ) {
+        id.hash(hasher);
+        if !visited.insert(id) {
+            return;
+        }
+
+        self.source(id).hash(hasher);
+        let mut dependencies: Vec<_> = self.dependencies(id).collect();
+        dependencies.sort_by(|a, b| a.to_str().cmp(b.to_str()));
+        for dependency in dependencies {
+            self.hash_document(dependency, visited, hasher);
+        }
+    }
+
+    /// Whether `id` or a document it depends on, directly or not, loads a resource. The contents
+    /// of resources are not part of the [fingerprint](Ast::fingerprint).
+    pub fn loads_resources(&self, id: &DocId) -> bool {
+        self.loads_resources_from(id, &mut HashSet::new())
+    }
+
+    fn loads_resources_from<'a>(&'a self, id: &'a DocId, visited: &mut HashSet<&'a DocId>) -> bool {
+        if !visited.insert(id) {
+            return false;
+        }
+
+        let statements = self.documents.get(id).into_iter().flatten();
+        statements
+            .map(Dependencies::of)
+            .any(|dependencies| dependencies.resources)
+            || self
+                .dependencies(id)
+                .any(|dependency| self.loads_resources_from(dependency, visited))
+    }
+
     pub fn root(&self) -> &DocId {
         &self.root
     }
@@ -66,23 +140,40 @@ impl Ast {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Statement {
     Variable(Variable, Span),
     CreatePart(Expression, Span),
+    Assert(Assert, Span),
+    Import(Import, Span),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Variable {
     pub name: String,
     pub value: Option<Expression>,
 }
 
+/// Binds the public variables of another document to `name`.
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub document: DocId,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Assert {
+    pub condition: Expression,
+    pub message: Option<Expression>,
+}
+
 impl Statement {
     pub fn walk_statement<T: StatementVisitor>(&self, visitor: &mut T) -> T::Result {
         match self {
             Statement::Variable(v, s) => visitor.visit_variable(v, s),
             Statement::CreatePart(v, s) => visitor.visit_create_part(v, s),
+            Statement::Assert(a, s) => visitor.visit_assert(a, s),
+            Statement::Import(i, s) => visitor.visit_import(i, s),
         }
     }
 }
@@ -92,17 +183,19 @@ impl Statement {
         match self {
             Statement::Variable(_, s) => s,
             Statement::CreatePart(_, s) => s,
+            Statement::Assert(_, s) => s,
+            Statement::Import(_, s) => s,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum CallPath {
     Function(Box<Expression>),
     Document(DocId),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Argument {
     Named(String, Box<Expression>),
     Unnamed(Box<Expression>),
@@ -117,7 +210,7 @@ impl Argument {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Expression {
     Literal(Literal, Span),
     Reference(Reference, Span),
@@ -130,37 +223,37 @@ pub enum Expression {
     Scope(NestedScope, Span),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Invocation {
     pub path: CallPath,
     pub arguments: VecDeque<Argument>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Reference {
     pub name: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Property {
     pub target: Box<Expression>,
     pub name: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Index {
     pub target: Box<Expression>,
     pub index: Box<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Map {
     pub identifier: String,
     pub range: Box<Expression>,
     pub action: Box<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Reduce {
     pub left: String,
     pub right: String,
@@ -169,14 +262,14 @@ pub struct Reduce {
     pub action: Box<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct If {
     pub condition: Box<Expression>,
     pub if_true: Box<Expression>,
     pub if_false: Box<Expression>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NestedScope {
     pub statements: Vec<Statement>,
 }
@@ -211,14 +304,14 @@ impl Expression {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Literal {
     Number(f64),
     Bool(bool),
     Text(String),
     List(Vec<Expression>),
-    Resource(Box<dyn Resource>),
-    Function(Rc<Vec<Statement>>),
+    Resource(Arc<dyn Resource>),
+    Function(Arc<Vec<Statement>>),
 }
 
 impl Literal {
@@ -1,10 +1,10 @@
 use crate::parser::{
-    Expression, If, Index, Invocation, Literal, Map, NestedScope, Property, Reduce, Reference,
-    Statement, Variable,
+    Assert, Expression, If, Import, Index, Invocation, Literal, Map, NestedScope, Property, Reduce,
+    Reference, Statement, Variable,
 };
 use crate::resources::Resource;
 use logos::Span;
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub trait StatementVisitor: Sized {
     type Result;
@@ -14,6 +14,8 @@ pub trait StatementVisitor: Sized {
     }
     fn visit_variable(&mut self, variable: &Variable, _span: &Span) -> Self::Result;
     fn visit_create_part(&mut self, expr: &Expression, _span: &Span) -> Self::Result;
+    fn visit_assert(&mut self, assert: &Assert, _span: &Span) -> Self::Result;
+    fn visit_import(&mut self, import: &Import, _span: &Span) -> Self::Result;
 }
 
 pub trait ExpressionVisitor: Sized {
@@ -44,5 +46,5 @@ pub trait LiteralVisitor: Sized {
     fn visit_text(&mut self, v: &str) -> Self::Result;
     fn visit_list(&mut self, v: &[Expression]) -> Self::Result;
     fn visit_resource(&mut self, v: &dyn Resource) -> Self::Result;
-    fn visit_function(&mut self, v: &Rc<Vec<Statement>>) -> Self::Result;
+    fn visit_function(&mut self, v: &Arc<Vec<Statement>>) -> Self::Result;
 }
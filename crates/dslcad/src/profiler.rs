@@ -0,0 +1,241 @@
+use crate::parser::{DocId, Reader};
+use crate::runtime::StackFrame;
+use crate::source::LineColExt;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::{Result, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const SNIPPET_LENGTH: usize = 60;
+
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD: u64 = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Wall time spent in a statement or builtin call, `cached` when its result was reused from the
+/// cache instead of evaluated.
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub name: String,
+    pub frame: StackFrame,
+    pub start: Duration,
+    pub duration: Duration,
+    pub thread: u64,
+    pub cached: bool,
+}
+
+/// Total time spent in one statement or builtin call across every time it ran.
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub name: String,
+    pub frame: StackFrame,
+    pub total: Duration,
+    pub calls: usize,
+    pub cached: usize,
+}
+
+/// Records timings of an evaluation.
+pub struct Profiler {
+    start: Instant,
+    events: Mutex<Vec<Event>>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            events: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn record(&self, name: &str, frame: StackFrame, start: Instant, cached: bool) {
+        let event = Event {
+            name: name.to_string(),
+            frame,
+            start: start.duration_since(self.start),
+            duration: start.elapsed(),
+            thread: THREAD.with(|t| *t),
+            cached,
+        };
+        self.events.lock().unwrap().push(event);
+    }
+
+    pub fn events(&self) -> Vec<Event> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Timings grouped by location, slowest first.
+    pub fn summary(&self) -> Vec<Summary> {
+        let mut grouped: HashMap<(&str, &DocId, usize, usize), Summary> = HashMap::new();
+        let events = self.events.lock().unwrap();
+        for event in events.iter() {
+            let key = (
+                event.name.as_str(),
+                &event.frame.document,
+                event.frame.span.start,
+                event.frame.span.end,
+            );
+            let summary = grouped.entry(key).or_insert_with(|| Summary {
+                name: event.name.clone(),
+                frame: event.frame.clone(),
+                total: Duration::ZERO,
+                calls: 0,
+                cached: 0,
+            });
+            summary.total += event.duration;
+            summary.calls += 1;
+            summary.cached += event.cached as usize;
+        }
+
+        let mut summary: Vec<_> = grouped.into_values().collect();
+        summary.sort_by(|a, b| b.total.cmp(&a.total));
+        summary
+    }
+
+    /// Print the slowest `limit` locations along with the source they point at.
+    pub fn write_report(
+        &self,
+        mut w: impl Write,
+        reader: &impl Reader,
+        limit: usize,
+    ) -> Result<()> {
+        writeln!(
+            w,
+            "{:>10} {:>6} {:>6}  location",
+            "total", "calls", "cached"
+        )?;
+        for summary in self.summary().into_iter().take(limit) {
+            let path = summary.frame.document.to_path();
+            write!(
+                w,
+                "{:>8.1}ms {:>6} {:>6}  {}",
+                summary.total.as_secs_f64() * 1000.,
+                summary.calls,
+                summary.cached,
+                path.to_string_lossy()
+            )?;
+
+            if let Ok(source) = reader.read(path) {
+                let (line, col) = summary.frame.span.line_col(&source);
+                write!(w, "[{}:{}-{}]", line, col.start, col.end)?;
+                write!(w, " {}", summary.name)?;
+                if let Some(snippet) = source.get(summary.frame.span.clone()) {
+                    write!(w, ": {}", Self::snippet(snippet))?;
+                }
+            } else {
+                write!(w, " {}", summary.name)?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    /// Write every event in the Chrome trace event format, viewable in `chrome://tracing`.
+    pub fn write_chrome_trace(&self, w: impl Write) -> Result<()> {
+        let events: Vec<_> = self
+            .events()
+            .iter()
+            .map(|event| {
+                let name = match event.cached {
+                    true => format!("{} (cached)", event.name),
+                    false => event.name.clone(),
+                };
+                json!({
+                    "name": name,
+                    "cat": event.frame.document.to_str(),
+                    "ph": "X",
+                    "ts": event.start.as_micros() as u64,
+                    "dur": event.duration.as_micros() as u64,
+                    "pid": 1,
+                    "tid": event.thread,
+                    "args": {
+                        "document": event.frame.document.to_path().to_string_lossy(),
+                        "start": event.frame.span.start,
+                        "end": event.frame.span.end,
+                        "cached": event.cached,
+                    },
+                })
+            })
+            .collect();
+
+        serde_json::to_writer(w, &json!({ "traceEvents": events }))?;
+        Ok(())
+    }
+
+    fn snippet(source: &str) -> String {
+        let line = source.lines().next().unwrap_or_default().trim();
+        if line.chars().count() > SNIPPET_LENGTH || source.trim().lines().count() > 1 {
+            let short: String = line.chars().take(SNIPPET_LENGTH).collect();
+            format!("{short}...")
+        } else {
+            line.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestReader;
+
+    fn frame(start: usize, end: usize) -> StackFrame {
+        StackFrame {
+            document: DocId::new("test.ds".to_string()),
+            span: start..end,
+        }
+    }
+
+    #[test]
+    fn it_sums_events_by_location() {
+        let profiler = Profiler::new();
+        let start = Instant::now();
+        profiler.record("cube", frame(0, 6), start, false);
+        profiler.record("cube", frame(0, 6), start, false);
+        profiler.record("statement", frame(0, 7), start, false);
+
+        let summary = profiler.summary();
+        assert_eq!(2, summary.len());
+        assert_eq!(2, summary.iter().find(|s| s.name == "cube").unwrap().calls);
+    }
+
+    #[test]
+    fn it_prints_reports_with_source() {
+        let profiler = Profiler::new();
+        profiler.record("cube", frame(0, 6), Instant::now(), false);
+        profiler.record("cube", frame(0, 6), Instant::now(), true);
+
+        let mut buf = Vec::new();
+        profiler
+            .write_report(&mut buf, &TestReader("cube();"), 10)
+            .unwrap();
+
+        let report = String::from_utf8(buf).unwrap();
+        assert!(report.contains("     2      1  test.ds[1:0-6] cube: cube()"));
+    }
+
+    #[test]
+    fn it_writes_chrome_traces() {
+        let profiler = Profiler::new();
+        profiler.record("cube", frame(0, 6), Instant::now(), false);
+        profiler.record("cube", frame(0, 6), Instant::now(), true);
+
+        let mut buf = Vec::new();
+        profiler.write_chrome_trace(&mut buf).unwrap();
+
+        let trace: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+        assert_eq!("cube", trace["traceEvents"][0]["name"]);
+        assert_eq!("X", trace["traceEvents"][0]["ph"]);
+        assert_eq!("cube (cached)", trace["traceEvents"][1]["name"]);
+        assert_eq!(true, trace["traceEvents"][1]["args"]["cached"]);
+    }
+}
@@ -0,0 +1,348 @@
+use crate::parser::{quote_string, Expression, Literal};
+use crate::suggestion::{did_you_mean, suggestion_message};
+use clap::ValueEnum;
+use dslcad_storage::threemf::Unit;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Name of the project manifest, looked up in a directory and its parents.
+pub const MANIFEST: &str = "dslcad.toml";
+
+/// A `dslcad.toml` listing the parts of a project and how to build them.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Project {
+    /// Directory containing the manifest, every path in it is relative to this.
+    #[serde(skip)]
+    pub root: PathBuf,
+    #[serde(default = "default_output")]
+    pub output: PathBuf,
+    #[serde(default)]
+    pub unit: Unit,
+    /// Directories searched for paths such as `<std>/fasteners/m3`.
+    #[serde(default)]
+    pub lib: Vec<PathBuf>,
+    #[serde(default)]
+    pub targets: IndexMap<String, Target>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Target {
+    pub source: PathBuf,
+    /// Named sets of arguments, the target is built once for each of them.
+    #[serde(default)]
+    pub arguments: IndexMap<String, IndexMap<String, Argument>>,
+    #[serde(default = "default_deflection")]
+    pub deflection: f64,
+    #[serde(default = "default_formats")]
+    pub formats: Vec<Format>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Argument {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    List(Vec<Argument>),
+}
+
+impl Argument {
+    pub fn to_literal(&self) -> Literal {
+        match self {
+            Argument::Bool(value) => Literal::Bool(*value),
+            Argument::Number(value) => Literal::Number(*value),
+            Argument::Text(value) => Literal::Text(value.clone()),
+            Argument::List(items) => Literal::List(
+                items
+                    .iter()
+                    .map(|item| Expression::Literal(item.to_literal(), 0..0))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Source of the value, as it would be written in a script or an `--argument`.
+impl Display for Argument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Argument::Bool(value) => write!(f, "{value}"),
+            Argument::Number(value) => write!(f, "{value}"),
+            Argument::Text(value) => write!(f, "{}", quote_string(value)),
+            Argument::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+pub enum Format {
+    #[default]
+    #[value(name = "3mf")]
+    #[serde(rename = "3mf")]
+    ThreeMf,
+    #[serde(rename = "raw")]
+    Raw,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::ThreeMf => "3mf",
+            Format::Raw => "bin",
+        }
+    }
+}
+
+/// A target built with one of its sets of arguments.
+#[derive(Debug, Clone)]
+pub struct Job {
+    /// File name of the outputs, without the extension.
+    pub name: String,
+    pub source: PathBuf,
+    pub arguments: IndexMap<String, Argument>,
+    pub deflection: f64,
+    pub formats: Vec<Format>,
+}
+
+impl Job {
+    pub fn arguments(&self) -> HashMap<&str, Expression> {
+        self.arguments
+            .iter()
+            .map(|(name, argument)| {
+                (
+                    name.as_str(),
+                    Expression::Literal(argument.to_literal(), 0..0),
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ProjectError {
+    #[error("could not find {MANIFEST} in {0} or its parents")]
+    NotFound(PathBuf),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("invalid {MANIFEST}: {0}")]
+    Manifest(#[from] toml::de::Error),
+    #[error("no target named '{0}'{}", suggestion_message(.1))]
+    UnknownTarget(String, Option<String>),
+    #[error("more than one target builds a job named '{0}'")]
+    DuplicateJob(String),
+}
+
+impl Project {
+    /// The manifest in `directory` or the closest of its parents, if there is one.
+    pub fn find(directory: &Path) -> Result<Option<Self>, ProjectError> {
+        directory
+            .ancestors()
+            .map(|directory| directory.join(MANIFEST))
+            .find(|path| path.is_file())
+            .map(|path| Self::load(&path))
+            .transpose()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ProjectError> {
+        let mut project = Self::from_toml(&std::fs::read_to_string(path)?)?;
+        project.root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        Ok(project)
+    }
+
+    pub fn from_toml(manifest: &str) -> Result<Self, ProjectError> {
+        Ok(toml::from_str(manifest)?)
+    }
+
+    pub fn output_directory(&self) -> PathBuf {
+        self.root.join(&self.output)
+    }
+
+    pub fn library_paths(&self) -> Vec<PathBuf> {
+        self.lib.iter().map(|path| self.root.join(path)).collect()
+    }
+
+    /// Every job needed to build `target`, or every target when it is `None`.
+    /// Job names are checked across every target since they also name the output files.
+    pub fn jobs(&self, target: Option<&str>) -> Result<Vec<Job>, ProjectError> {
+        let mut jobs: IndexMap<&str, Vec<Job>> = self
+            .targets
+            .iter()
+            .map(|(name, target)| (name.as_str(), target.jobs(name, &self.root)))
+            .collect();
+
+        let mut names = HashSet::new();
+        for job in jobs.values().flatten() {
+            if !names.insert(job.name.as_str()) {
+                return Err(ProjectError::DuplicateJob(job.name.clone()));
+            }
+        }
+
+        match target {
+            Some(name) => jobs.shift_remove(name).ok_or_else(|| {
+                ProjectError::UnknownTarget(
+                    name.to_string(),
+                    did_you_mean(name, self.targets.keys().map(String::as_str)),
+                )
+            }),
+            None => Ok(jobs.into_values().flatten().collect()),
+        }
+    }
+}
+
+impl Target {
+    fn jobs(&self, name: &str, root: &Path) -> Vec<Job> {
+        let job = |name: String, arguments: &IndexMap<String, Argument>| Job {
+            name,
+            source: root.join(&self.source),
+            arguments: arguments.clone(),
+            deflection: self.deflection,
+            formats: self.formats.clone(),
+        };
+
+        if self.arguments.is_empty() {
+            return vec![job(name.to_string(), &IndexMap::new())];
+        }
+        self.arguments
+            .iter()
+            .map(|(set, arguments)| job(format!("{name}-{set}"), arguments))
+            .collect()
+    }
+}
+
+fn default_output() -> PathBuf {
+    PathBuf::from("build")
+}
+
+fn default_deflection() -> f64 {
+    0.01
+}
+
+fn default_formats() -> Vec<Format> {
+    vec![Format::ThreeMf]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT: &str = r#"
+        unit = "inch"
+        lib = ["lib"]
+
+        [targets.bracket]
+        source = "parts/bracket.ds"
+        formats = ["3mf", "raw"]
+
+        [targets.bracket.arguments.small]
+        width = 10
+        label = "S"
+
+        [targets.bracket.arguments.large]
+        width = 20.5
+        hollow = true
+
+        [targets.lid]
+        source = "lid.ds"
+        deflection = 0.1
+    "#;
+
+    #[test]
+    fn it_lists_jobs() {
+        let mut project = Project::from_toml(PROJECT).unwrap();
+        project.root = PathBuf::from("project");
+
+        assert_eq!(Unit::Inch, project.unit);
+        assert_eq!(vec![PathBuf::from("project/lib")], project.library_paths());
+        assert_eq!(PathBuf::from("project/build"), project.output_directory());
+
+        let jobs = project.jobs(None).unwrap();
+        let names: Vec<_> = jobs.iter().map(|job| job.name.as_str()).collect();
+        assert_eq!(vec!["bracket-small", "bracket-large", "lid"], names);
+
+        assert_eq!(PathBuf::from("project/parts/bracket.ds"), jobs[0].source);
+        assert_eq!(vec![Format::ThreeMf, Format::Raw], jobs[0].formats);
+        assert_eq!(Some(&Argument::Number(10.)), jobs[0].arguments.get("width"));
+        assert_eq!(
+            Some(&Argument::Text("S".to_string())),
+            jobs[0].arguments.get("label")
+        );
+        assert_eq!(Some(&Argument::Bool(true)), jobs[1].arguments.get("hollow"));
+        assert_eq!(0.1, jobs[2].deflection);
+        assert_eq!(vec![Format::ThreeMf], jobs[2].formats);
+    }
+
+    #[test]
+    fn it_selects_targets() {
+        let project = Project::from_toml(PROJECT).unwrap();
+
+        assert_eq!(1, project.jobs(Some("lid")).unwrap().len());
+        assert!(matches!(
+            project.jobs(Some("lids")),
+            Err(ProjectError::UnknownTarget(_, Some(suggestion))) if suggestion == "lid"
+        ));
+        assert!(matches!(
+            Project::from_toml("[targets.lid]\nsorce = \"lid.ds\""),
+            Err(ProjectError::Manifest(_))
+        ));
+    }
+
+    #[test]
+    fn it_writes_arguments_as_source() {
+        let argument = Argument::List(vec![
+            Argument::Text(r"C:\new".to_string()),
+            Argument::Text("tab\tquote\"".to_string()),
+        ]);
+        let source = format!("a={argument}");
+
+        let arguments = crate::parse_arguments([source.as_str()].into_iter()).unwrap();
+        let Some(Expression::Literal(Literal::List(items), _)) = arguments.get("a") else {
+            panic!("expected a list");
+        };
+        let texts: Vec<_> = items
+            .iter()
+            .map(|item| match item {
+                Expression::Literal(Literal::Text(text), _) => text.as_str(),
+                _ => panic!("expected text"),
+            })
+            .collect();
+        assert_eq!(vec![r"C:\new", "tab\tquote\""], texts);
+    }
+
+    #[test]
+    fn it_rejects_duplicate_job_names() {
+        let project = Project::from_toml(
+            r#"
+            [targets.a]
+            source = "a.ds"
+            [targets.a.arguments.b]
+            width = 1
+
+            [targets.a-b]
+            source = "b.ds"
+        "#,
+        )
+        .unwrap();
+
+        for target in [None, Some("a-b")] {
+            assert!(matches!(
+                project.jobs(target),
+                Err(ProjectError::DuplicateJob(name)) if name == "a-b"
+            ));
+        }
+    }
+}
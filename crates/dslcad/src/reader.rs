@@ -17,4 +17,8 @@ impl Reader for FsReader {
     fn normalize(&self, path: &Path) -> PathBuf {
         path.to_path_buf().canonicalize().unwrap()
     }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.is_file()
+    }
 }
@@ -1,15 +1,24 @@
+mod csv_loader;
+mod data;
 mod ini_loader;
+mod json_loader;
 mod stl_loader;
+mod toml_loader;
 
-use crate::parser::{DocumentParseError, Parser, Reader};
+use crate::parser::{Parser, Reader};
 use crate::runtime::{RuntimeError, Value};
 use std::fmt::Debug;
+use std::path::Path;
 
+use crate::resources::csv_loader::CsvLoader;
 use crate::resources::ini_loader::IniLoader;
+use crate::resources::json_loader::JsonLoader;
+use crate::resources::toml_loader::TomlLoader;
 pub use stl_loader::StlLoader;
 
 pub trait ResourceLoader<TReader: Reader> {
-    fn load(&self, path: &str, reader: &TReader) -> Result<Box<dyn Resource>, DocumentParseError>;
+    /// Load the resource at `path`, failing with the reason it could not be read.
+    fn load(&self, path: &str, reader: &TReader) -> Result<Box<dyn Resource>, String>;
 }
 
 pub trait Resource: Debug + Send + Sync {
@@ -24,5 +33,12 @@ impl<R: Reader> ResourceExt for Parser<R> {
     fn with_default_loaders(self) -> Self {
         self.with_loader("stl", StlLoader)
             .with_loader("ini", IniLoader)
+            .with_loader("json", JsonLoader)
+            .with_loader("toml", TomlLoader)
+            .with_loader("csv", CsvLoader)
     }
 }
+
+fn read_text<R: Reader>(path: &str, reader: &R) -> Result<String, String> {
+    reader.read(Path::new(path)).map_err(|e| e.to_string())
+}
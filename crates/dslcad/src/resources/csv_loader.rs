@@ -0,0 +1,144 @@
+use crate::parser::Reader;
+use crate::resources::data::Data;
+use crate::resources::{read_text, Resource, ResourceLoader};
+
+/// Loads a CSV file with a header row as a list of records, one for every following row.
+pub struct CsvLoader;
+
+impl<R: Reader> ResourceLoader<R> for CsvLoader {
+    fn load(&self, path: &str, reader: &R) -> Result<Box<dyn Resource>, String> {
+        let mut rows = parse_rows(&read_text(path, reader)?)
+            .map_err(|e| e.to_string())?
+            .into_iter();
+        let header: Vec<String> = rows
+            .next()
+            .ok_or("missing header row")?
+            .into_iter()
+            .map(|field| field.text.trim().to_string())
+            .collect();
+
+        let mut records = Vec::new();
+        for (i, row) in rows.enumerate() {
+            if row.len() != header.len() {
+                return Err(format!(
+                    "row {} has {} fields but the header has {}",
+                    i + 2,
+                    row.len(),
+                    header.len()
+                ));
+            }
+            let fields = header
+                .iter()
+                .cloned()
+                .zip(row.into_iter().map(Field::into_data))
+                .collect();
+            records.push(Data::Record(fields));
+        }
+        Ok(Box::new(Data::List(records)))
+    }
+}
+
+#[derive(Default)]
+struct Field {
+    text: String,
+    /// Quoted fields are always text, so `"10"` is not read as a number.
+    quoted: bool,
+}
+
+impl Field {
+    fn into_data(self) -> Data {
+        match self.quoted {
+            true => Data::Text(self.text),
+            false => Data::from_text(&self.text),
+        }
+    }
+
+    fn is_blank(&self) -> bool {
+        !self.quoted && self.text.trim().is_empty()
+    }
+}
+
+/// Split CSV text into rows of fields, skipping empty lines. Fields in double quotes may contain
+/// commas, line breaks and `""` for a quote.
+fn parse_rows(text: &str) -> Result<Vec<Vec<Field>>, &'static str> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = Field::default();
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_blank() => {
+                field.text.clear();
+                field.quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.text.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => field.text.push(c),
+                        None => return Err("unterminated quoted field"),
+                    }
+                }
+            }
+            ',' => row.push(std::mem::take(&mut field)),
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                let line = std::mem::take(&mut row);
+                if !matches!(&line[..], [field] if field.is_blank()) {
+                    rows.push(line);
+                }
+            }
+            '\r' => {}
+            c if field.quoted && !c.is_whitespace() => return Err("text after a quoted field"),
+            _ if field.quoted => {}
+            c => field.text.push(c),
+        }
+    }
+    if !row.is_empty() || !field.is_blank() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestReader;
+
+    #[test]
+    fn it_splits_rows() {
+        let rows = parse_rows("a, \"b, \"\"c\"\"\"\n\n1,\"2\nline\"\r\n").unwrap();
+        let texts: Vec<Vec<&str>> = rows
+            .iter()
+            .map(|row| row.iter().map(|field| field.text.as_str()).collect())
+            .collect();
+        assert_eq!(vec![vec!["a", "b, \"c\""], vec!["1", "2\nline"]], texts);
+        assert!(rows[1][1].quoted);
+
+        assert!(parse_rows("\"a").is_err());
+        assert!(parse_rows("\"a\"b").is_err());
+    }
+
+    #[test]
+    fn it_loads_records() {
+        let resource = CsvLoader
+            .load(
+                "sizes.csv",
+                &TestReader("name,head,metric\nM3,5.5,true\n\"4\",7,false"),
+            )
+            .unwrap();
+        let Ok(crate::runtime::Value::List(records)) = resource.to_instance() else {
+            panic!("expected a list of records");
+        };
+        assert_eq!(2, records.len());
+
+        assert!(matches!(
+            CsvLoader.load("sizes.csv", &TestReader("name,head\nM3")),
+            Err(reason) if reason.contains("row 2")
+        ));
+    }
+}
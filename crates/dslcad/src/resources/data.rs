@@ -0,0 +1,87 @@
+use crate::resources::Resource;
+use crate::runtime::{RuntimeError, ScriptInstance, Value};
+use indexmap::IndexMap;
+use std::sync::Arc;
+
+/// Values read from a data file, records become scripts whose fields are accessed like
+/// `data.name`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Data {
+    Number(f64),
+    Bool(bool),
+    Text(String),
+    List(Vec<Data>),
+    Record(IndexMap<String, Data>),
+}
+
+impl Data {
+    /// Value of untyped text such as a CSV field, which may be a number or boolean.
+    pub fn from_text(text: &str) -> Self {
+        let text = text.trim();
+        match text {
+            "true" => Data::Bool(true),
+            "false" => Data::Bool(false),
+            _ => match text.parse::<f64>() {
+                // `inf` and `nan` parse as numbers but are more likely to be text
+                Ok(number) if number.is_finite() => Data::Number(number),
+                _ => Data::Text(text.to_string()),
+            },
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Data::Number(value) => Value::Number(*value),
+            Data::Bool(value) => Value::Bool(*value),
+            Data::Text(value) => Value::Text(value.clone()),
+            Data::List(items) => Value::List(items.iter().map(Data::to_value).collect()),
+            Data::Record(fields) => {
+                let variables = fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.to_value()))
+                    .collect();
+                Value::Script(Arc::new(ScriptInstance::record(variables)))
+            }
+        }
+    }
+}
+
+impl Resource for Data {
+    fn to_instance(&self) -> Result<Value, RuntimeError> {
+        Ok(self.to_value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::Access;
+
+    #[test]
+    fn it_types_text() {
+        assert_eq!(Data::Number(-2.5), Data::from_text(" -2.5 "));
+        assert_eq!(Data::Bool(false), Data::from_text("false"));
+        assert_eq!(Data::Text("inf".to_string()), Data::from_text("inf"));
+        assert_eq!(Data::Text("M3".to_string()), Data::from_text("M3"));
+    }
+
+    #[test]
+    fn it_makes_records_into_scripts() {
+        let data = Data::Record(IndexMap::from([
+            ("size".to_string(), Data::Number(3.)),
+            (
+                "holes".to_string(),
+                Data::List(vec![Data::Bool(true), Data::Text("a".to_string())]),
+            ),
+        ]));
+
+        let Ok(Value::Script(record)) = data.to_instance() else {
+            panic!("expected a script");
+        };
+        assert_eq!(
+            Some(Ok(3.)),
+            record.get("size").map(|size| size.to_number())
+        );
+        assert!(matches!(record.get("holes"), Some(Value::List(holes)) if holes.len() == 2));
+    }
+}
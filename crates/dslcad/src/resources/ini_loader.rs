@@ -1,39 +1,88 @@
-use crate::parser::{DocumentParseError, Reader};
-use crate::resources::{Resource, ResourceLoader};
-use crate::runtime::{RuntimeError, ScriptInstance, Value};
-use std::collections::HashMap;
-use std::path::Path;
-use std::rc::Rc;
+use crate::parser::Reader;
+use crate::resources::data::Data;
+use crate::resources::{read_text, Resource, ResourceLoader};
+use indexmap::IndexMap;
 
+/// Loads `key = value` lines as a record, with a nested record for every `[section]`.
 pub struct IniLoader;
 
 impl<R: Reader> ResourceLoader<R> for IniLoader {
-    fn load(&self, path: &str, reader: &R) -> Result<Box<dyn Resource>, DocumentParseError> {
-        let data = reader.read(Path::new(path)).unwrap();
-
-        let mut values = HashMap::new();
-        for line in data.lines() {
-            let mut parts = line.split('=');
-            if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
-                values.insert(key.trim().to_string(), value.trim().to_string());
-            }
+    fn load(&self, path: &str, reader: &R) -> Result<Box<dyn Resource>, String> {
+        let data = parse(&read_text(path, reader)?).map_err(|e| e.to_string())?;
+        Ok(Box::new(data))
+    }
+}
+
+fn parse(text: &str) -> Result<Data, String> {
+    let mut values = IndexMap::new();
+    let mut sections: IndexMap<String, IndexMap<String, Data>> = IndexMap::new();
+    let mut section = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.entry(name.trim().to_string()).or_default();
+            section = Some(name.trim().to_string());
+            continue;
         }
 
-        Ok(Box::new(values))
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => (key.trim(), value.trim()),
+            _ => return Err(format!("line {} is not a key=value pair", i + 1)),
+        };
+        let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(text) => Data::Text(text.to_string()),
+            None => Data::from_text(value),
+        };
+
+        let values = match &section {
+            Some(section) => &mut sections[section],
+            None => &mut values,
+        };
+        values.insert(key.to_string(), value);
     }
+
+    values.extend(
+        sections
+            .into_iter()
+            .map(|(name, section)| (name, Data::Record(section))),
+    );
+    Ok(Data::Record(values))
 }
 
-impl Resource for HashMap<String, String> {
-    fn to_instance(&self) -> Result<Value, RuntimeError> {
-        let variables = self
-            .clone()
-            .drain()
-            .map(|(k, v)| (k, Value::Text(v)))
-            .collect();
-
-        Ok(Value::Script(Rc::new(ScriptInstance::new(
-            vec![Value::List(Vec::new())],
-            variables,
-        )?)))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_ini() {
+        let data = parse(
+            "; fasteners\nname = Test Name\nsize=3\n\n[m3]\nhead = 5.5\nlabel = \"10\"\nmetric = true",
+        )
+        .unwrap();
+
+        let m3 = Data::Record(IndexMap::from([
+            ("head".to_string(), Data::Number(5.5)),
+            ("label".to_string(), Data::Text("10".to_string())),
+            ("metric".to_string(), Data::Bool(true)),
+        ]));
+        assert_eq!(
+            Data::Record(IndexMap::from([
+                ("name".to_string(), Data::Text("Test Name".to_string())),
+                ("size".to_string(), Data::Number(3.)),
+                ("m3".to_string(), m3),
+            ])),
+            data
+        );
+
+        assert_eq!(
+            Err("line 2 is not a key=value pair".to_string()),
+            parse("a = 1\nb")
+        );
+        assert!(parse("= 1").is_err());
     }
 }
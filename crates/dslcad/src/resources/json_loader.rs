@@ -0,0 +1,60 @@
+use crate::parser::Reader;
+use crate::resources::data::Data;
+use crate::resources::{read_text, Resource, ResourceLoader};
+use serde_json::Value as Json;
+
+pub struct JsonLoader;
+
+impl<R: Reader> ResourceLoader<R> for JsonLoader {
+    fn load(&self, path: &str, reader: &R) -> Result<Box<dyn Resource>, String> {
+        let json = serde_json::from_str(&read_text(path, reader)?).map_err(|e| e.to_string())?;
+        Ok(Box::new(data(json).map_err(|e| e.to_string())?))
+    }
+}
+
+fn data(json: Json) -> Result<Data, &'static str> {
+    Ok(match json {
+        Json::Null => return Err("null values are not supported"),
+        Json::Bool(value) => Data::Bool(value),
+        Json::Number(value) => Data::Number(value.as_f64().ok_or("number out of range")?),
+        Json::String(value) => Data::Text(value),
+        Json::Array(items) => Data::List(items.into_iter().map(data).collect::<Result<_, _>>()?),
+        Json::Object(fields) => Data::Record(
+            fields
+                .into_iter()
+                .map(|(name, value)| Ok((name, data(value)?)))
+                .collect::<Result<_, _>>()?,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestReader;
+    use indexmap::IndexMap;
+
+    fn load(json: &'static str) -> Result<Box<dyn Resource>, String> {
+        JsonLoader.load("data.json", &TestReader(json))
+    }
+
+    #[test]
+    fn it_loads_json() {
+        let data = data(serde_json::from_str(r#"{"m3": {"head": 5.5, "sizes": [6, 8]}}"#).unwrap());
+        let m3 = Data::Record(IndexMap::from([
+            ("head".to_string(), Data::Number(5.5)),
+            (
+                "sizes".to_string(),
+                Data::List(vec![Data::Number(6.), Data::Number(8.)]),
+            ),
+        ]));
+        assert_eq!(
+            Ok(Data::Record(IndexMap::from([("m3".to_string(), m3)]))),
+            data
+        );
+
+        assert!(load("[1, true, \"a\"]").is_ok());
+        assert!(load("{\"a\": null}").is_err());
+        assert!(load("{\"a\": ").is_err());
+    }
+}
@@ -1,20 +1,21 @@
-use crate::parser::{DocumentParseError, Reader};
+use crate::parser::Reader;
 use crate::resources::{Resource, ResourceLoader};
 use crate::runtime::{RuntimeError, Value};
 use dslcad_occt::{Point, TriangleMesh};
 use std::io::Cursor;
 use std::path::Path;
-use std::rc::Rc;
+use std::sync::Arc;
 use stl_io::IndexedMesh;
 
 pub struct StlLoader;
 
 impl<R: Reader> ResourceLoader<R> for StlLoader {
-    fn load(&self, path: &str, reader: &R) -> Result<Box<dyn Resource>, DocumentParseError> {
-        let data = reader.read_bytes(Path::new(path)).unwrap();
+    fn load(&self, path: &str, reader: &R) -> Result<Box<dyn Resource>, String> {
+        let data = reader
+            .read_bytes(Path::new(path))
+            .map_err(|e| e.to_string())?;
         let mut cursor = Cursor::new(&data);
-        let mesh =
-            stl_io::read_stl(&mut cursor).map_err(|_| DocumentParseError::UnexpectedEndOfFile())?;
+        let mesh = stl_io::read_stl(&mut cursor).map_err(|e| e.to_string())?;
         Ok(Box::new(mesh))
     }
 }
@@ -28,6 +29,6 @@ impl Resource for IndexedMesh {
             self.faces.iter().map(|f| f.vertices),
         );
 
-        Ok(Value::Shape(Rc::new(mesh.try_into()?)))
+        Ok(Value::Shape(Arc::new(mesh.try_into()?)))
     }
 }
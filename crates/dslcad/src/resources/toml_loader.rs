@@ -0,0 +1,57 @@
+use crate::parser::Reader;
+use crate::resources::data::Data;
+use crate::resources::{read_text, Resource, ResourceLoader};
+use toml::{Table, Value as Toml};
+
+pub struct TomlLoader;
+
+impl<R: Reader> ResourceLoader<R> for TomlLoader {
+    fn load(&self, path: &str, reader: &R) -> Result<Box<dyn Resource>, String> {
+        let table: Table = toml::from_str(&read_text(path, reader)?).map_err(|e| e.to_string())?;
+        Ok(Box::new(data(Toml::Table(table))))
+    }
+}
+
+fn data(toml: Toml) -> Data {
+    match toml {
+        Toml::String(value) => Data::Text(value),
+        Toml::Integer(value) => Data::Number(value as f64),
+        Toml::Float(value) => Data::Number(value),
+        Toml::Boolean(value) => Data::Bool(value),
+        Toml::Datetime(value) => Data::Text(value.to_string()),
+        Toml::Array(items) => Data::List(items.into_iter().map(data).collect()),
+        Toml::Table(fields) => Data::Record(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name, data(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TestReader;
+    use indexmap::IndexMap;
+
+    #[test]
+    fn it_loads_toml() {
+        let table: Table =
+            toml::from_str("[m3]\nhead = 5.5\nsizes = [6, 8]\nmetric = true").unwrap();
+        let m3 = Data::Record(IndexMap::from([
+            ("head".to_string(), Data::Number(5.5)),
+            (
+                "sizes".to_string(),
+                Data::List(vec![Data::Number(6.), Data::Number(8.)]),
+            ),
+            ("metric".to_string(), Data::Bool(true)),
+        ]));
+        assert_eq!(
+            Data::Record(IndexMap::from([("m3".to_string(), m3)])),
+            data(Toml::Table(table))
+        );
+
+        assert!(TomlLoader.load("data.toml", &TestReader("a = ")).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 mod access;
+mod cache;
 mod output;
 mod runtime_error;
 mod scope;
@@ -7,23 +8,26 @@ mod stack;
 mod types;
 mod value;
 
-use crate::library::{ArgValue, CallSignature, Library};
+use crate::library::{ArgValue, CallSignature, Function as Builtin, Library};
 use crate::parser::*;
+use crate::profiler::Profiler;
 use crate::runtime::scope::Scope;
+use crate::testing::{Assertion, Assertions};
 use log::trace;
 use logos::Span;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
-use std::rc::Rc;
+use std::sync::Arc;
 use std::time::Instant;
 
 pub use access::Access;
-pub use stack::WithStack;
+pub use cache::Cache;
+pub use stack::{StackFrame, WithStack};
 pub use types::Type;
 pub use value::Value;
 
 use crate::resources::Resource;
-use crate::runtime::stack::{Stack, StackFrame};
+use crate::runtime::stack::Stack;
 use crate::runtime::value::Function;
 pub use runtime_error::RuntimeError;
 pub use script_instance::ScriptInstance;
@@ -36,6 +40,9 @@ pub struct Engine<'a> {
     stack: Stack,
     scope: Scope,
     current_document: Option<DocId>,
+    cache: Option<&'a Cache>,
+    profiler: Option<&'a Profiler>,
+    assertions: Option<&'a Assertions>,
 }
 
 impl<'a> Engine<'a> {
@@ -46,24 +53,39 @@ impl<'a> Engine<'a> {
             stack: Stack::new(),
             scope: Scope::default(),
             current_document: None,
+            cache: None,
+            profiler: None,
+            assertions: None,
         }
     }
 
+    /// Reuse builtin results stored in `cache` and store new ones into it.
+    pub fn with_cache(mut self, cache: &'a Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Record the time spent in every statement and builtin call in `profiler`.
+    pub fn with_profiler(mut self, profiler: &'a Profiler) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
+    /// Record every assertion in `assertions` and keep going when one fails.
+    pub fn with_assertions(mut self, assertions: &'a Assertions) -> Self {
+        self.assertions = Some(assertions);
+        self
+    }
+
     pub fn eval_root(
         &mut self,
-        arguments: HashMap<&'a str, Literal>,
+        arguments: HashMap<&'a str, Expression>,
     ) -> Result<Value, WithStack<RuntimeError>> {
         let root = self.ast.root().clone();
         let arguments = arguments
             .into_iter()
-            .map(|(k, v)| {
-                (
-                    k,
-                    self.visit_expression(&Expression::Literal(v, Span::default()))
-                        .unwrap(),
-                )
-            })
-            .collect();
+            .map(|(k, v)| Ok((k, self.visit_expression(&v)?)))
+            .collect::<Result<_, WithStack<RuntimeError>>>()?;
         Ok(self
             .with_scope(Scope::new(arguments), |e| e.eval(root))?
             .into())
@@ -78,6 +100,17 @@ impl<'a> Engine<'a> {
         self.eval_statements(id, statements)
     }
 
+    /// Evaluate a document for its public variables, it does not have to draw anything.
+    fn eval_module(&mut self, id: DocId) -> Result<ScriptInstance, WithStack<RuntimeError>> {
+        self.current_document = Some(id.clone());
+        let statements = self.ast.documents.get(&id).ok_or_else(|| {
+            WithStack::from_err(RuntimeError::UnknownIdentifier(id.to_string()), &self.stack)
+        })?;
+
+        self.run_statements(&id, statements)?;
+        Ok(ScriptInstance::module(self.scope.clone()))
+    }
+
     fn with_scope<T>(&mut self, scope: Scope, f: impl FnOnce(&mut Self) -> T) -> T {
         let tmp = self.scope.clone();
         self.scope = scope;
@@ -86,32 +119,249 @@ impl<'a> Engine<'a> {
         ret
     }
 
+    /// Copy of this engine evaluating in `scope`, used to evaluate independent expressions on
+    /// other threads.
+    fn fork(&self, scope: Scope) -> Engine<'a> {
+        Engine {
+            library: self.library,
+            ast: self.ast,
+            stack: self.stack.clone(),
+            scope,
+            current_document: self.current_document.clone(),
+            cache: self.cache,
+            profiler: self.profiler,
+            assertions: self.assertions,
+        }
+    }
+
     fn eval_statements(
         &mut self,
         id: DocId,
         statements: &[Statement],
     ) -> Result<ScriptInstance, WithStack<RuntimeError>> {
+        let ret = self.run_statements(&id, statements)?;
+        ScriptInstance::from_scope(ret, self.scope.clone())
+            .map_err(|e| WithStack::from_err(e, &self.stack))
+    }
+
+    /// Evaluate statements into the current scope, returning the parts they draw.
+    fn run_statements(
+        &mut self,
+        id: &DocId,
+        statements: &[Statement],
+    ) -> Result<Vec<Value>, WithStack<RuntimeError>> {
         let mut ret = Vec::new();
 
-        for statement in statements {
-            self.stack.push(StackFrame::from_statement(&id, statement));
+        for run in Self::independent_runs(statements) {
+            if let [statement] = run {
+                if let Some(v) = self.eval_frame(id, statement)? {
+                    ret.push(v);
+                }
+                continue;
+            }
+
+            let results = par_map(run, |statement| {
+                let mut engine = self.fork(self.scope.clone());
+                let value = engine.eval_frame(id, statement)?;
+                let variable = match statement {
+                    Statement::Variable(Variable { name, .. }, _)
+                    | Statement::Import(Import { name, .. }, _) => engine
+                        .scope
+                        .get(name)
+                        .cloned()
+                        .map(|v| (name.to_string(), v)),
+                    Statement::CreatePart(..) | Statement::Assert(..) => None,
+                };
+                Ok((value, variable))
+            });
 
-            if self.stack.len() >= MAX_STACK_SIZE {
-                return Err(WithStack::from_err(
-                    RuntimeError::StackOverflow(),
-                    &self.stack,
-                ));
+            for result in results {
+                let (value, variable) = result?;
+                if let Some((name, v)) = variable {
+                    self.scope.set(name, v);
+                }
+                if let Some(v) = value {
+                    ret.push(v);
+                }
             }
+        }
+        Ok(ret)
+    }
 
-            if let Some(v) = self.visit_statement(statement)? {
-                ret.push(v);
+    /// Split statements into runs in which no statement references a variable declared earlier in
+    /// the same run, so the statements of a run can be evaluated in any order.
+    fn independent_runs(statements: &[Statement]) -> Vec<&[Statement]> {
+        let mut runs = Vec::new();
+        let mut start = 0;
+        let mut declared = HashSet::new();
+
+        for (i, statement) in statements.iter().enumerate() {
+            let dependencies = Dependencies::of(statement);
+            if dependencies
+                .references
+                .iter()
+                .any(|name| declared.contains(name.as_str()))
+            {
+                runs.push(&statements[start..i]);
+                start = i;
+                declared.clear();
             }
+            if let Statement::Variable(Variable { name, .. }, _)
+            | Statement::Import(Import { name, .. }, _) = statement
+            {
+                declared.insert(name.as_str());
+            }
+        }
 
-            self.stack.pop();
+        if start < statements.len() {
+            runs.push(&statements[start..]);
         }
+        runs
+    }
 
-        ScriptInstance::from_scope(ret, self.scope.clone())
-            .map_err(|e| WithStack::from_err(e, &self.stack))
+    fn eval_frame(
+        &mut self,
+        id: &DocId,
+        statement: &Statement,
+    ) -> Result<Option<Value>, WithStack<RuntimeError>> {
+        self.stack.push(StackFrame::from_statement(id, statement));
+
+        if self.stack.len() >= MAX_STACK_SIZE {
+            return Err(WithStack::from_err(
+                RuntimeError::StackOverflow(),
+                &self.stack,
+            ));
+        }
+
+        let timer = Instant::now();
+        let (value, cached) = self.eval_statement(id, statement)?;
+        if let Some(profiler) = self.profiler {
+            profiler.record(
+                "statement",
+                StackFrame::from_statement(id, statement),
+                timer,
+                cached,
+            );
+        }
+        self.stack.pop();
+        Ok(value)
+    }
+
+    /// Evaluate a statement, reusing the previous result of top level statements that neither
+    /// changed nor depend on anything that changed. Also returns whether the result was reused.
+    fn eval_statement(
+        &mut self,
+        id: &DocId,
+        statement: &Statement,
+    ) -> Result<(Option<Value>, bool), WithStack<RuntimeError>> {
+        let Some((cache, key)) = self.cache.zip(self.statement_key(id, statement)) else {
+            return Ok((self.visit_statement(statement)?, false));
+        };
+
+        if let Some(value) = cache.get_statement(key) {
+            trace!("{id}:{:?} reused", statement.span());
+            let value = match statement {
+                Statement::Variable(Variable { name, .. }, _)
+                | Statement::Import(Import { name, .. }, _) => {
+                    self.scope.set(name.to_string(), value);
+                    None
+                }
+                Statement::CreatePart(..) => Some(value),
+                Statement::Assert(..) => unreachable!("assertions are not cached"),
+            };
+            return Ok((value, true));
+        }
+
+        let result = self.visit_statement(statement)?;
+        let value = match statement {
+            Statement::Variable(Variable { name, .. }, _)
+            | Statement::Import(Import { name, .. }, _) => self.scope.get(name).cloned(),
+            Statement::CreatePart(..) => result.clone(),
+            Statement::Assert(..) => None,
+        };
+        if let Some(value) = value {
+            cache.insert_statement(key, &value);
+        }
+        Ok((result, false))
+    }
+
+    fn statement_key(&self, id: &DocId, statement: &Statement) -> Option<u64> {
+        let cache = self.cache?;
+        // only statements at the top of a document have source to compare against
+        let text = self.ast.source(id)?.get(statement.span().clone())?;
+        match statement {
+            Statement::Variable(Variable { value: None, .. }, _) => return None,
+            // assertions run every time so they are recorded by every evaluation
+            Statement::Assert(..) => return None,
+            _ => {}
+        }
+
+        let dependencies = Dependencies::of(statement);
+        let loads_resources = dependencies.resources
+            || dependencies
+                .documents
+                .iter()
+                .any(|document| self.ast.loads_resources(document));
+        if loads_resources {
+            return None;
+        }
+
+        let references: Vec<_> = dependencies
+            .references
+            .iter()
+            .map(|name| (name.as_str(), self.scope.get(name)))
+            .collect();
+        let documents: Vec<_> = dependencies
+            .documents
+            .iter()
+            .map(|document| self.ast.fingerprint(document))
+            .collect();
+
+        cache.statement_key(text, &references, &documents)
+    }
+
+    fn call_builtin(
+        &self,
+        name: &str,
+        function: &Builtin,
+        arguments: &HashMap<&str, Value>,
+        span: &Span,
+    ) -> Result<Value, RuntimeError> {
+        let timer = Instant::now();
+        let result = self.call_cached(name, function, arguments);
+
+        if let (Some(profiler), Some(frame)) = (self.profiler, self.stack.last()) {
+            let frame = StackFrame {
+                document: frame.document.clone(),
+                span: span.clone(),
+            };
+            profiler.record(name, frame, timer, matches!(result, Ok((_, true))));
+        }
+        result.map(|(value, _)| value)
+    }
+
+    /// Call a builtin, also returning whether its result was loaded from the cache.
+    fn call_cached(
+        &self,
+        name: &str,
+        function: &Builtin,
+        arguments: &HashMap<&str, Value>,
+    ) -> Result<(Value, bool), RuntimeError> {
+        let Some((cache, key)) = self
+            .cache
+            .and_then(|cache| Some((cache, cache.key(name, arguments)?)))
+        else {
+            return Ok((function(arguments)?, false));
+        };
+
+        if let Some(value) = cache.get(key) {
+            trace!("{name}(..) loaded from cache");
+            return Ok((value, true));
+        }
+
+        let value = function(arguments)?;
+        cache.insert(key, &value);
+        Ok((value, false))
     }
 
     fn named_argument_values(
@@ -135,6 +385,21 @@ impl<'a> Engine<'a> {
     }
 }
 
+#[cfg(feature = "rayon")]
+fn par_map<T: Send, R: Send>(
+    items: impl IntoIterator<Item = T>,
+    f: impl Fn(T) -> R + Sync + Send,
+) -> Vec<R> {
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+    let items: Vec<T> = items.into_iter().collect();
+    items.into_par_iter().map(f).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn par_map<T, R>(items: impl IntoIterator<Item = T>, f: impl Fn(T) -> R) -> Vec<R> {
+    items.into_iter().map(f).collect()
+}
+
 impl StatementVisitor for Engine<'_> {
     type Result = Result<Option<Value>, WithStack<RuntimeError>>;
 
@@ -170,6 +435,57 @@ impl StatementVisitor for Engine<'_> {
     fn visit_create_part(&mut self, expr: &Expression, _span: &Span) -> Self::Result {
         Ok(Some(self.visit_expression(expr)?))
     }
+
+    fn visit_import(&mut self, Import { document, name }: &Import, _span: &Span) -> Self::Result {
+        let current_document = self.current_document.clone();
+        let module = self.with_scope(Scope::default(), |e| e.eval_module(document.clone()));
+        self.current_document = current_document;
+        let module = module?;
+        self.scope
+            .set(name.to_string(), Value::Script(Arc::new(module)));
+        Ok(None)
+    }
+
+    fn visit_assert(
+        &mut self,
+        Assert { condition, message }: &Assert,
+        _span: &Span,
+    ) -> Self::Result {
+        let passed = self
+            .visit_expression(condition)?
+            .to_bool()
+            .map_err(|e| WithStack::from_err(e, &self.stack))?;
+        if passed && self.assertions.is_none() {
+            return Ok(None);
+        }
+
+        let message = match message {
+            Some(message) => self
+                .visit_expression(message)?
+                .to_text()
+                .map_err(|e| WithStack::from_err(e, &self.stack))?,
+            None => self
+                .stack
+                .last()
+                .and_then(|frame| self.ast.source(&frame.document))
+                .and_then(|source| source.get(condition.span().clone()))
+                .unwrap_or_default()
+                .to_string(),
+        };
+
+        if let (Some(assertions), Some(frame)) = (self.assertions, self.stack.last()) {
+            assertions.record(Assertion {
+                frame: frame.clone(),
+                message,
+                passed,
+            });
+            return Ok(None);
+        }
+        Err(WithStack::from_err(
+            RuntimeError::AssertionFailed(message),
+            &self.stack,
+        ))
+    }
 }
 
 impl ExpressionVisitor for Engine<'_> {
@@ -185,7 +501,7 @@ impl ExpressionVisitor for Engine<'_> {
         if let Some(value) = scope.get(&l.name) {
             Ok(value.clone())
         } else if self.library.contains(&l.name) {
-            Ok(Value::Function(Rc::new(Function::Builtin {
+            Ok(Value::Function(Arc::new(Function::Builtin {
                 name: l.name.to_string(),
             })))
         } else {
@@ -199,7 +515,7 @@ impl ExpressionVisitor for Engine<'_> {
     fn visit_invocation(
         &mut self,
         Invocation { arguments, path }: &Invocation,
-        _s: &Span,
+        s: &Span,
     ) -> Self::Result {
         let argument_values = arguments.iter().try_fold(Vec::new(), |mut acc, argument| {
             match argument {
@@ -230,7 +546,8 @@ impl ExpressionVisitor for Engine<'_> {
                             .library
                             .find(CallSignature::new(name, argument_values))
                             .map_err(|e| WithStack::from_err(e, &self.stack))?;
-                        f(&a).map_err(|e| WithStack::from_err(e, &self.stack))?
+                        self.call_builtin(name, f, &a, s)
+                            .map_err(|e| WithStack::from_err(e, &self.stack))?
                     }
                     Function::Defined {
                         clojure,
@@ -263,7 +580,7 @@ impl ExpressionVisitor for Engine<'_> {
                     .map_err(|e| WithStack::from_err(e, &self.stack))?;
                 let v =
                     self.with_scope(Scope::new(named_argument_values), |e| e.eval(id.clone()))?;
-                Ok(Value::Script(Rc::new(v)))
+                Ok(Value::Script(Arc::new(v)))
             }
         }
     }
@@ -304,15 +621,13 @@ impl ExpressionVisitor for Engine<'_> {
             .to_list()
             .map_err(|e| WithStack::from_err(e, &self.stack))?;
 
-        let mut loop_scope = scope.clone();
-        let mut results = Vec::new();
-        for v in range_value {
-            loop_scope.set(l.identifier.to_string(), v.clone());
-            let eval = self.with_scope(loop_scope.clone(), |e| e.visit_expression(&l.action))?;
-            results.push(eval);
-        }
+        let results = par_map(range_value, |v| {
+            let mut loop_scope = scope.clone();
+            loop_scope.set(l.identifier.to_string(), v);
+            self.fork(loop_scope).visit_expression(&l.action)
+        });
 
-        Ok(Value::List(results))
+        Ok(Value::List(results.into_iter().collect::<Result<_, _>>()?))
     }
 
     fn visit_reduce(&mut self, l: &Reduce, _s: &Span) -> Self::Result {
@@ -358,7 +673,7 @@ impl ExpressionVisitor for Engine<'_> {
     fn visit_scope(&mut self, l: &NestedScope, _s: &Span) -> Self::Result {
         let document = self.current_document.as_ref().map(|d| d.to_string());
         let inst = self.eval_statements(DocId::new_with_path("scope", document), &l.statements)?;
-        Ok(Value::Script(Rc::new(inst)))
+        Ok(Value::Script(Arc::new(inst)))
     }
 }
 
@@ -390,8 +705,8 @@ impl LiteralVisitor for Engine<'_> {
             .map_err(|e| WithStack::from_err(e, &self.stack))
     }
 
-    fn visit_function(&mut self, v: &Rc<Vec<Statement>>) -> Self::Result {
-        Ok(Value::Function(Rc::new(Function::Defined {
+    fn visit_function(&mut self, v: &Arc<Vec<Statement>>) -> Self::Result {
+        Ok(Value::Function(Arc::new(Function::Defined {
             clojure: self.scope.clone(),
             statements: v.clone(),
         })))
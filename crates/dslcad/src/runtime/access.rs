@@ -1,5 +1,5 @@
 use super::value::Value;
-use dslcad_occt::{Point, Shape, Wire};
+use dslcad_occt::{Face, Point, Shape, Wire};
 
 pub trait Access {
     fn get(&self, identifier: &str) -> Option<Value>;
@@ -14,11 +14,24 @@ impl Access for Wire {
     }
 }
 
+impl Access for Face {
+    fn get(&self, identifier: &str) -> Option<Value> {
+        match identifier {
+            "center" => Some(self.center_of_mass().into()),
+            "area" => Some(self.area().into()),
+            _ => None,
+        }
+    }
+}
+
 impl Access for Shape {
     fn get(&self, identifier: &str) -> Option<Value> {
         match identifier {
             "center" => Some(self.center_of_mass().into()),
             "volume" => Some(self.volume().into()),
+            "min" => self.bounds().map(|(min, _)| min.into()),
+            "max" => self.bounds().map(|(_, max)| max.into()),
+            "size" => self.bounds().map(|(min, max)| (max - min).into()),
             _ => None,
         }
     }
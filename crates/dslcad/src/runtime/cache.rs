@@ -0,0 +1,458 @@
+use super::Value;
+use dslcad_occt::{DsShape, Face, Shape, Wire};
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+/// Content addressed store of builtin results and top level statements.
+///
+/// Results are keyed on the builtin name and the hash of its arguments. Shared values such as
+/// geometry and scripts are hashed by the key of the call that produced them, so values that did
+/// not come out of the cache can not be used as part of a key.
+///
+/// Entries that were not used since the last call to [Cache::evict_untouched] are dropped by it,
+/// so a long running preview only keeps the results of its latest evaluation.
+#[derive(Default)]
+pub struct Cache {
+    entries: Mutex<HashMap<u64, Value>>,
+    statements: Mutex<HashMap<u64, Value>>,
+    provenance: Mutex<HashMap<usize, u64>>,
+    touched: Mutex<HashSet<u64>>,
+    directory: Option<PathBuf>,
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Persist geometry as BREP files in `directory` so it survives between runs. Files are only
+    /// removed by [Cache::prune_directory].
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len() + self.statements.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn key(&self, name: &str, arguments: &HashMap<&str, Value>) -> Option<u64> {
+        let mut hasher = Fnv1a::default();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        name.hash(&mut hasher);
+
+        let mut names: Vec<_> = arguments.keys().collect();
+        names.sort();
+        for name in names {
+            name.hash(&mut hasher);
+            self.hash_value(&arguments[name], &mut hasher)?;
+        }
+
+        Some(hasher.finish())
+    }
+
+    /// Key of a statement with source `text`, the values of the names it references and the
+    /// fingerprints of the documents it invokes.
+    pub(crate) fn statement_key(
+        &self,
+        text: &str,
+        references: &[(&str, Option<&Value>)],
+        documents: &[u64],
+    ) -> Option<u64> {
+        let mut hasher = Fnv1a::default();
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        text.hash(&mut hasher);
+
+        for (name, value) in references {
+            name.hash(&mut hasher);
+            match value {
+                Some(value) => self.hash_value(value, &mut hasher)?,
+                None => 0u8.hash(&mut hasher),
+            }
+        }
+        documents.hash(&mut hasher);
+
+        Some(hasher.finish())
+    }
+
+    /// Drop every entry that was not read or written since the previous call.
+    pub fn evict_untouched(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        let mut statements = self.statements.lock().unwrap();
+        let mut provenance = self.provenance.lock().unwrap();
+        let mut touched = self.touched.lock().unwrap();
+
+        entries.retain(|key, _| touched.contains(key));
+        statements.retain(|key, _| touched.contains(key));
+
+        // forget the addresses of dropped values, they may be reused by new values
+        let alive: HashSet<_> = entries
+            .values()
+            .chain(statements.values())
+            .filter_map(Self::address)
+            .collect();
+        provenance.retain(|address, _| alive.contains(address));
+
+        debug!(
+            "evicted cache down to {} entries",
+            entries.len() + statements.len()
+        );
+        touched.clear();
+    }
+
+    /// Delete the BREP files in the cache directory that were not written or loaded within
+    /// `max_age`.
+    pub fn prune_directory(&self, max_age: Duration) {
+        let Some(directory) = &self.directory else {
+            return;
+        };
+        let Ok(files) = std::fs::read_dir(directory) else {
+            return;
+        };
+
+        let now = SystemTime::now();
+        for file in files.flatten() {
+            let path = file.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("brep") {
+                continue;
+            }
+
+            let age = file
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .map(|modified| now.duration_since(modified).unwrap_or_default());
+            if matches!(age, Ok(age) if age > max_age) {
+                debug!("pruning {} from cache", path.to_string_lossy());
+                if let Err(e) = std::fs::remove_file(&path) {
+                    warn!("could not prune {}: {e}", path.to_string_lossy());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn get_statement(&self, key: u64) -> Option<Value> {
+        let value = self.statements.lock().unwrap().get(&key).cloned()?;
+        self.touch(key);
+        Some(value)
+    }
+
+    pub(crate) fn insert_statement(&self, key: u64, value: &Value) {
+        if let Some(address) = Self::address(value) {
+            self.provenance.lock().unwrap().insert(address, key);
+        }
+        self.statements.lock().unwrap().insert(key, value.clone());
+        self.touch(key);
+    }
+
+    pub(crate) fn get(&self, key: u64) -> Option<Value> {
+        let value = self.entries.lock().unwrap().get(&key).cloned();
+        if let Some(value) = value {
+            self.touch(key);
+            return Some(value);
+        }
+
+        let value = self.load(key)?;
+        self.remember(key, &value);
+        Some(value)
+    }
+
+    pub(crate) fn insert(&self, key: u64, value: &Value) {
+        if Self::geometry_kind(value).is_none() {
+            return;
+        }
+
+        self.remember(key, value);
+        self.store(key, value);
+    }
+
+    fn remember(&self, key: u64, value: &Value) {
+        if let Some(address) = Self::address(value) {
+            self.provenance.lock().unwrap().insert(address, key);
+        }
+        self.entries.lock().unwrap().insert(key, value.clone());
+        self.touch(key);
+    }
+
+    fn touch(&self, key: u64) {
+        self.touched.lock().unwrap().insert(key);
+    }
+
+    fn hash_value(&self, value: &Value, hasher: &mut impl Hasher) -> Option<()> {
+        match value {
+            Value::Number(n) => {
+                0u8.hash(hasher);
+                n.to_bits().hash(hasher);
+            }
+            Value::Bool(b) => {
+                1u8.hash(hasher);
+                b.hash(hasher);
+            }
+            Value::Text(t) => {
+                2u8.hash(hasher);
+                t.hash(hasher);
+            }
+            Value::Point(p) => {
+                3u8.hash(hasher);
+                for coordinate in [p.x(), p.y(), p.z()] {
+                    coordinate.to_bits().hash(hasher);
+                }
+            }
+            Value::List(list) => {
+                4u8.hash(hasher);
+                list.len().hash(hasher);
+                for item in list {
+                    self.hash_value(item, hasher)?;
+                }
+            }
+            Value::Line(_)
+            | Value::Face(_)
+            | Value::Shape(_)
+            | Value::Function(_)
+            | Value::Script(_) => {
+                5u8.hash(hasher);
+                let address = Self::address(value)?;
+                self.provenance.lock().unwrap().get(&address)?.hash(hasher);
+            }
+        }
+        Some(())
+    }
+
+    /// Identity of shared values. Every value with an address is kept alive by the cache, so an
+    /// address can not be reused by another value while it is known.
+    fn address(value: &Value) -> Option<usize> {
+        match value {
+            Value::Line(l) => Some(Arc::as_ptr(l) as usize),
+            Value::Face(f) => Some(Arc::as_ptr(f) as usize),
+            Value::Shape(s) => Some(Arc::as_ptr(s) as usize),
+            Value::Function(f) => Some(Arc::as_ptr(f) as usize),
+            Value::Script(s) => Some(Arc::as_ptr(s) as usize),
+            _ => None,
+        }
+    }
+
+    fn geometry_kind(value: &Value) -> Option<&'static str> {
+        match value {
+            Value::Line(_) => Some("edge"),
+            Value::Face(_) => Some("face"),
+            Value::Shape(_) => Some("shape"),
+            _ => None,
+        }
+    }
+
+    fn path(directory: &Path, key: u64, kind: &str) -> PathBuf {
+        directory.join(format!("{key:016x}.{kind}.brep"))
+    }
+
+    fn store(&self, key: u64, value: &Value) {
+        let (Some(directory), Some(kind)) = (&self.directory, Self::geometry_kind(value)) else {
+            return;
+        };
+
+        let path = Self::path(directory, key, kind);
+        if path.exists() {
+            return;
+        }
+
+        let result = std::fs::create_dir_all(directory)
+            .map_err(|e| e.to_string())
+            .and_then(|_| {
+                match value {
+                    Value::Line(l) => l.write_brep(&path),
+                    Value::Face(f) => f.write_brep(&path),
+                    Value::Shape(s) => s.write_brep(&path),
+                    _ => Ok(()),
+                }
+                .map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            warn!("could not cache {}: {e}", path.to_string_lossy());
+        }
+    }
+
+    fn load(&self, key: u64) -> Option<Value> {
+        let directory = self.directory.as_ref()?;
+
+        for kind in ["edge", "face", "shape"] {
+            let path = Self::path(directory, key, kind);
+            if !path.exists() {
+                continue;
+            }
+
+            let value = match kind {
+                "edge" => Wire::read_brep(&path).map(Value::from),
+                "face" => Face::read_brep(&path).map(Value::from),
+                _ => Shape::read_brep(&path).map(Value::from),
+            };
+
+            match value {
+                Ok(value) => {
+                    debug!("loaded {} from cache", path.to_string_lossy());
+                    // loading counts as a use so pruning keeps the file
+                    let used = File::options()
+                        .append(true)
+                        .open(&path)
+                        .and_then(|file| file.set_modified(SystemTime::now()));
+                    if let Err(e) = used {
+                        warn!("could not mark {} as used: {e}", path.to_string_lossy());
+                    }
+                    return Some(value);
+                }
+                Err(e) => warn!("could not load {}: {e}", path.to_string_lossy()),
+            }
+        }
+
+        None
+    }
+}
+
+/// FNV-1a hasher, unlike `DefaultHasher` its output does not change between Rust releases or
+/// targets so keys of geometry persisted on disk stay valid.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    // lengths are hashed at the same width on 32 and 64 bit targets
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_hashes_equal_arguments_to_the_same_key() {
+        let cache = Cache::new();
+        let a = HashMap::from([("x", Value::Number(1.)), ("y", Value::Number(2.))]);
+        let b = HashMap::from([("y", Value::Number(2.)), ("x", Value::Number(1.))]);
+
+        assert_eq!(cache.key("cube", &a), cache.key("cube", &b));
+        assert_ne!(cache.key("cube", &a), cache.key("sphere", &a));
+    }
+
+    #[test]
+    fn it_hashes_different_arguments_to_different_keys() {
+        let cache = Cache::new();
+        let a = HashMap::from([("x", Value::Number(1.))]);
+        let b = HashMap::from([("x", Value::Number(2.))]);
+        let c = HashMap::from([("x", Value::Text(String::from("1")))]);
+
+        assert_ne!(cache.key("cube", &a), cache.key("cube", &b));
+        assert_ne!(cache.key("cube", &a), cache.key("cube", &c));
+    }
+
+    #[test]
+    fn it_only_stores_geometry() {
+        let cache = Cache::new();
+        cache.insert(1, &Value::Number(1.));
+
+        assert!(cache.is_empty());
+        assert!(cache.get(1).is_none());
+    }
+
+    #[test]
+    fn it_keys_statements_on_referenced_values() {
+        let cache = Cache::new();
+        let one = Value::Number(1.);
+        let two = Value::Number(2.);
+
+        let a = cache.statement_key("var y = x;", &[("x", Some(&one))], &[]);
+        let b = cache.statement_key("var y = x;", &[("x", Some(&two))], &[]);
+        let c = cache.statement_key("var y = x;", &[("x", Some(&one))], &[42]);
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+
+        let script = Value::List(vec![]);
+        cache.insert_statement(a.unwrap(), &script);
+        assert_eq!(1, cache.len());
+    }
+
+    #[test]
+    fn it_keeps_keys_stable() {
+        let cache = Cache::new();
+        let a = cache.statement_key("1;", &[], &[]);
+        assert_eq!(a, cache.statement_key("1;", &[], &[]));
+
+        let mut hasher = Fnv1a::default();
+        hasher.write(b"a");
+        assert_eq!(0xaf63dc4c8601ec8c, hasher.finish());
+
+        let (mut a, mut b) = (Fnv1a::default(), Fnv1a::default());
+        a.write_usize(3);
+        b.write_u64(3);
+        assert_eq!(a.finish(), b.finish());
+    }
+
+    #[test]
+    fn it_evicts_untouched_entries() {
+        let cache = Cache::new();
+        cache.insert_statement(1, &Value::Number(1.));
+        cache.insert_statement(2, &Value::Number(2.));
+        cache.evict_untouched();
+        assert_eq!(2, cache.len());
+
+        assert!(cache.get_statement(1).is_some());
+        cache.evict_untouched();
+        assert_eq!(1, cache.len());
+        assert!(cache.get_statement(2).is_none());
+
+        cache.evict_untouched();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn it_prunes_old_files() {
+        let directory = std::env::temp_dir().join(format!("dslcad-cache-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let (old, new, other) = (
+            directory.join("old.shape.brep"),
+            directory.join("new.shape.brep"),
+            directory.join("notes.txt"),
+        );
+        for path in [&old, &new, &other] {
+            File::create(path).unwrap();
+        }
+        let long_ago = SystemTime::now() - Duration::from_secs(60 * 60);
+        for path in [&old, &other] {
+            let file = File::options().append(true).open(path).unwrap();
+            file.set_modified(long_ago).unwrap();
+        }
+
+        Cache::new()
+            .with_directory(&directory)
+            .prune_directory(Duration::from_secs(60));
+        let (old, new, other) = (old.exists(), new.exists(), other.exists());
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(!old);
+        assert!(new);
+        assert!(other);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::elapsed;
-use dslcad_occt::{Error, Point, Shape, Wire};
+use dslcad_occt::{Error, Face, Mesh, Point, Shape, Wire};
 use dslcad_storage::protocol::Part;
 
 pub trait IntoPart {
@@ -28,24 +28,41 @@ impl IntoPart for &Wire {
     }
 }
 
-impl IntoPart for &Shape {
+impl IntoPart for &Face {
     fn into_part(self, deflection: f64) -> Result<Part, Error> {
         let original = elapsed!("generated mesh", self.mesh(deflection)?);
-        let mut mesh = dslcad_storage::protocol::Mesh {
-            vertices: original.vertices.clone(),
-            triangles: vec![],
-            normals: vec![],
-        };
 
-        for (tri, normal) in original.triangles_with_normals() {
-            mesh.triangles.push(*tri);
-            mesh.normals.push(normal);
-        }
+        Ok(Part::Face {
+            points: self.points()?,
+            lines: self.lines(deflection)?,
+            mesh: to_protocol_mesh(&original),
+        })
+    }
+}
+
+impl IntoPart for &Shape {
+    fn into_part(self, deflection: f64) -> Result<Part, Error> {
+        let original = elapsed!("generated mesh", self.mesh(deflection)?);
 
         Ok(Part::Object {
             points: elapsed!("generated points", self.points()?),
             lines: elapsed!("generated lines", self.lines(deflection)?),
-            mesh,
+            mesh: to_protocol_mesh(&original),
         })
     }
 }
+
+fn to_protocol_mesh(original: &Mesh) -> dslcad_storage::protocol::Mesh {
+    let mut mesh = dslcad_storage::protocol::Mesh {
+        vertices: original.vertices.clone(),
+        triangles: vec![],
+        normals: vec![],
+    };
+
+    for (tri, normal) in original.triangles_with_normals() {
+        mesh.triangles.push(*tri);
+        mesh.normals.push(normal);
+    }
+
+    mesh
+}
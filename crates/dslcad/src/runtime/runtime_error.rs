@@ -1,3 +1,5 @@
+use crate::runtime::Type;
+use crate::suggestion::suggestion_message;
 use dslcad_occt::Error;
 use thiserror::Error;
 
@@ -9,8 +11,10 @@ pub enum RuntimeError {
     UnsetParameter(String),
     #[error("could not find property {0}")]
     MissingProperty(String),
-    #[error("mismatched types")]
-    UnexpectedType(),
+    #[error("values of type {0} have no properties")]
+    NoProperties(Type),
+    #[error("mismatched types, expected {expected} but found {actual}")]
+    UnexpectedType { expected: Type, actual: Type },
     #[error("script did not return a value")]
     NoReturnValue(),
     #[error("reduce must have at least one value")]
@@ -19,8 +23,11 @@ pub enum RuntimeError {
     StackOverflow(),
     #[error(transparent)]
     Opencascade(dslcad_occt::Error),
-    #[error("could not find function with name '{name}'")]
-    CouldNotFindFunction { name: String },
+    #[error("could not find function with name '{name}'{}", suggestion_message(.suggestion))]
+    CouldNotFindFunction {
+        name: String,
+        suggestion: Option<String>,
+    },
     #[error("could not find default argument for function '{name}'")]
     UnknownDefaultArgument { name: String },
     #[error("multi part scripts must all use the same type")]
@@ -32,10 +39,44 @@ pub enum RuntimeError {
         target: String,
         options: Vec<String>,
     },
+    #[error("invalid value '{value}' for argument '{name}', expected {expected}")]
+    InvalidArgument {
+        name: String,
+        value: String,
+        expected: String,
+    },
     #[error("can not build arc with two identical points")]
     ArcWithIdenticalPoints(),
     #[error("{0}")]
     UserDefined(String),
+    #[error("assertion failed: {0}")]
+    AssertionFailed(String),
+}
+
+impl RuntimeError {
+    /// Stable identifier of the error for tools that consume `--message-format json`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RuntimeError::UnknownIdentifier(_) => "UnknownIdentifier",
+            RuntimeError::UnsetParameter(_) => "UnsetParameter",
+            RuntimeError::MissingProperty(_) => "MissingProperty",
+            RuntimeError::NoProperties(_) => "NoProperties",
+            RuntimeError::UnexpectedType { .. } => "UnexpectedType",
+            RuntimeError::NoReturnValue() => "NoReturnValue",
+            RuntimeError::EmptyReduce() => "EmptyReduce",
+            RuntimeError::StackOverflow() => "StackOverflow",
+            RuntimeError::Opencascade(_) => "Opencascade",
+            RuntimeError::CouldNotFindFunction { .. } => "CouldNotFindFunction",
+            RuntimeError::UnknownDefaultArgument { .. } => "UnknownDefaultArgument",
+            RuntimeError::MismatchedPartTypes => "MismatchedPartTypes",
+            RuntimeError::InvalidMultiPartType => "InvalidMultiPartType",
+            RuntimeError::CouldNotFindFunctionSignature { .. } => "CouldNotFindFunctionSignature",
+            RuntimeError::InvalidArgument { .. } => "InvalidArgument",
+            RuntimeError::ArcWithIdenticalPoints() => "ArcWithIdenticalPoints",
+            RuntimeError::UserDefined(_) => "UserDefined",
+            RuntimeError::AssertionFailed(_) => "AssertionFailed",
+        }
+    }
 }
 
 impl From<dslcad_occt::Error> for RuntimeError {
@@ -14,6 +14,27 @@ impl ScriptInstance {
         Self::new(parts, scope.variables)
     }
 
+    /// Instance of an imported document, exposing every variable not starting with `_`.
+    pub fn module(scope: Scope) -> Self {
+        let variables = scope
+            .variables
+            .into_iter()
+            .filter(|(name, _)| !name.starts_with('_'))
+            .collect();
+        ScriptInstance {
+            variables,
+            parts: Value::List(Vec::new()),
+        }
+    }
+
+    /// Instance holding only `variables`, such as a record read from a data file.
+    pub fn record(variables: HashMap<String, Value>) -> Self {
+        ScriptInstance {
+            variables,
+            parts: Value::List(Vec::new()),
+        }
+    }
+
     pub fn new(
         mut parts: Vec<Value>,
         variables: HashMap<String, Value>,
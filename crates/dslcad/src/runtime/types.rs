@@ -1,6 +1,8 @@
+use serde::Serialize;
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Type {
     Number,
     Bool,
@@ -8,6 +10,7 @@ pub enum Type {
     List,
     Point,
     Edge,
+    Face,
     Shape,
     Function,
 }
@@ -21,6 +24,7 @@ impl Display for Type {
             Type::List => f.write_str("list"),
             Type::Point => f.write_str("point"),
             Type::Edge => f.write_str("edge"),
+            Type::Face => f.write_str("face"),
             Type::Shape => f.write_str("shape"),
             Type::Function => f.write_str("function"),
         }
@@ -1,14 +1,14 @@
 use crate::runtime::output::IntoPart;
 use dslcad_storage::protocol::Part;
 use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
+use std::sync::Arc;
 
 use super::Access;
 use super::Type;
 use crate::parser::Statement;
 use crate::runtime::scope::Scope;
 use crate::runtime::{RuntimeError, ScriptInstance};
-use dslcad_occt::{DsShape, Point, Shape, Wire};
+use dslcad_occt::{DsShape, Face, Point, Shape, Wire};
 
 type Result<T> = std::result::Result<T, RuntimeError>;
 
@@ -18,15 +18,16 @@ pub enum Value {
     Bool(bool),
     Text(String),
 
-    Point(Rc<Point>),
-    Line(Rc<Wire>),
-    Shape(Rc<Shape>),
+    Point(Arc<Point>),
+    Line(Arc<Wire>),
+    Face(Arc<Face>),
+    Shape(Arc<Shape>),
 
     List(Vec<Value>),
 
-    Function(Rc<Function>),
+    Function(Arc<Function>),
 
-    Script(Rc<ScriptInstance>),
+    Script(Arc<ScriptInstance>),
 }
 
 #[derive(Clone)]
@@ -36,27 +37,31 @@ pub enum Function {
     },
     Defined {
         clojure: Scope,
-        statements: Rc<Vec<Statement>>,
+        statements: Arc<Vec<Statement>>,
     },
 }
 
-unsafe impl Send for Value {}
-
 impl From<Point> for Value {
     fn from(value: Point) -> Self {
-        Value::Point(Rc::new(value))
+        Value::Point(Arc::new(value))
     }
 }
 
 impl From<Wire> for Value {
     fn from(value: Wire) -> Self {
-        Value::Line(Rc::new(value))
+        Value::Line(Arc::new(value))
+    }
+}
+
+impl From<Face> for Value {
+    fn from(value: Face) -> Self {
+        Value::Face(Arc::new(value))
     }
 }
 
 impl From<Shape> for Value {
     fn from(value: Shape) -> Self {
-        Value::Shape(Rc::new(value))
+        Value::Shape(Arc::new(value))
     }
 }
 
@@ -84,33 +89,39 @@ impl From<Vec<Value>> for Value {
     }
 }
 
-impl From<Rc<Point>> for Value {
-    fn from(value: Rc<Point>) -> Self {
+impl From<Arc<Point>> for Value {
+    fn from(value: Arc<Point>) -> Self {
         Value::Point(value)
     }
 }
 
-impl From<Rc<Wire>> for Value {
-    fn from(value: Rc<Wire>) -> Self {
+impl From<Arc<Wire>> for Value {
+    fn from(value: Arc<Wire>) -> Self {
         Value::Line(value)
     }
 }
 
-impl From<Rc<Shape>> for Value {
-    fn from(value: Rc<Shape>) -> Self {
+impl From<Arc<Face>> for Value {
+    fn from(value: Arc<Face>) -> Self {
+        Value::Face(value)
+    }
+}
+
+impl From<Arc<Shape>> for Value {
+    fn from(value: Arc<Shape>) -> Self {
         Value::Shape(value)
     }
 }
 
-impl From<Rc<Function>> for Value {
-    fn from(value: Rc<Function>) -> Self {
+impl From<Arc<Function>> for Value {
+    fn from(value: Arc<Function>) -> Self {
         Value::Function(value)
     }
 }
 
 impl From<ScriptInstance> for Value {
     fn from(value: ScriptInstance) -> Self {
-        Value::Script(Rc::new(value))
+        Value::Script(Arc::new(value))
     }
 }
 
@@ -130,11 +141,15 @@ impl Debug for Value {
                 .field("z", &p.x())
                 .finish(),
             Value::Line(_) => f.debug_tuple("Line").finish(),
+            Value::Face(_) => f.debug_tuple("Face").finish(),
             Value::Function(_) => f.debug_tuple("Func").finish(),
         }
     }
 }
 
+/// Conversion cost of changing a value into another type, such as a closed edge into a face.
+const CHANGE_TYPE_COST: usize = 2;
+
 impl Value {
     pub fn flatten(&self) -> Vec<&Value> {
         match self {
@@ -143,6 +158,7 @@ impl Value {
             Value::Text(_) => vec![self],
             Value::Point(_) => vec![self],
             Value::Line(_) => vec![self],
+            Value::Face(_) => vec![self],
             Value::Shape(_) => vec![self],
             Value::List(list) => list.iter().flat_map(|l| l.flatten()).collect(),
             Value::Script(s) => s.value().flatten(),
@@ -155,6 +171,7 @@ impl Value {
             Value::Number(_) | Value::Bool(_) | Value::Text(_) => Ok(Part::Empty),
             Value::Point(p) => Ok(p.into_part(deflection)?),
             Value::Line(l) => Ok(l.into_part(deflection)?),
+            Value::Face(f) => Ok(f.into_part(deflection)?),
             Value::Shape(s) => Ok(s.into_part(deflection)?),
             _ => {
                 panic!("can not be turned into Part directly, use `flatten` first")
@@ -167,7 +184,7 @@ impl Value {
             Value::Number(f) => Ok(*f),
             Value::Script(i) => i.value().to_number(),
             Value::List(l) if l.len() == 1 => l[0].to_number(),
-            _ => Err(RuntimeError::UnexpectedType()),
+            _ => Err(self.unexpected(Type::Number)),
         }
     }
 
@@ -179,8 +196,8 @@ impl Value {
                 .iter()
                 .filter_map(|i| i.to_text().ok())
                 .reduce(|a, b| format!("{a}\n{b}"))
-                .ok_or(RuntimeError::UnexpectedType())?),
-            _ => Err(RuntimeError::UnexpectedType()),
+                .ok_or_else(|| self.unexpected(Type::Text))?),
+            _ => Err(self.unexpected(Type::Text)),
         }
     }
 
@@ -189,7 +206,7 @@ impl Value {
             Value::Bool(f) => Ok(*f),
             Value::Script(i) => i.value().to_bool(),
             Value::List(l) if l.len() == 1 => l[0].to_bool(),
-            _ => Err(RuntimeError::UnexpectedType()),
+            _ => Err(self.unexpected(Type::Bool)),
         }
     }
 
@@ -197,42 +214,59 @@ impl Value {
         match self {
             Value::Script(i) => Ok(i.as_ref()),
             Value::Line(w) => Ok(w.as_ref()),
+            Value::Face(f) => Ok(f.as_ref()),
             Value::Shape(s) => Ok(s.as_ref()),
             Value::Point(p) => Ok(p.as_ref()),
-            _ => Err(RuntimeError::UnexpectedType()),
+            _ => Err(RuntimeError::NoProperties(self.get_type())),
         }
     }
 
-    pub fn to_point(&self) -> Result<Rc<Point>> {
+    pub fn to_point(&self) -> Result<Arc<Point>> {
         match self {
             Value::Point(s) => Ok(s.clone()),
             Value::Script(i) => i.value().to_point(),
             Value::List(l) if l.len() == 1 => l[0].to_point(),
-            _ => Err(RuntimeError::UnexpectedType()),
+            _ => Err(self.unexpected(Type::Point)),
         }
     }
 
-    pub fn to_line(&self) -> Result<Rc<Wire>> {
+    pub fn to_line(&self) -> Result<Arc<Wire>> {
         match self {
             Value::Line(s) => Ok(s.clone()),
             Value::Script(i) => i.value().to_line(),
             Value::List(values) => {
                 let lines: Vec<_> = values.iter().filter_map(|v| v.to_line().ok()).collect();
-                Self::fuse_list(&lines)
+                self.fuse_list(&lines, Type::Edge)
+            }
+            _ => Err(self.unexpected(Type::Edge)),
+        }
+    }
+
+    pub fn to_face(&self) -> Result<Arc<Face>> {
+        match self {
+            Value::Face(f) => Ok(f.clone()),
+            Value::Line(l) => Ok(Arc::new(Face::from_wire(l)?)),
+            Value::Script(i) => i.value().to_face(),
+            Value::List(values) => {
+                let faces = values
+                    .iter()
+                    .map(|v| v.to_face())
+                    .collect::<Result<Vec<_>>>()?;
+                self.fuse_list(&faces, Type::Face)
             }
-            _ => Err(RuntimeError::UnexpectedType()),
+            _ => Err(self.unexpected(Type::Face)),
         }
     }
 
-    pub fn to_shape(&self) -> Result<Rc<Shape>> {
+    pub fn to_shape(&self) -> Result<Arc<Shape>> {
         match self {
             Value::Shape(s) => Ok(s.clone()),
             Value::Script(i) => i.value().to_shape(),
             Value::List(values) => {
                 let shapes: Vec<_> = values.iter().filter_map(|v| v.to_shape().ok()).collect();
-                Self::fuse_list(&shapes)
+                self.fuse_list(&shapes, Type::Shape)
             }
-            _ => Err(RuntimeError::UnexpectedType()),
+            _ => Err(self.unexpected(Type::Shape)),
         }
     }
 
@@ -240,15 +274,38 @@ impl Value {
         match self {
             Value::List(s) => Ok(s.clone()),
             Value::Script(i) => i.value().to_list(),
-            _ => Err(RuntimeError::UnexpectedType()),
+            _ => Err(self.unexpected(Type::List)),
         }
     }
 
-    pub fn to_function(&self) -> Result<Rc<Function>> {
+    pub fn to_function(&self) -> Result<Arc<Function>> {
         match self {
             Value::Function(s) => Ok(s.clone()),
             Value::Script(i) => i.value().to_function(),
-            _ => Err(RuntimeError::UnexpectedType()),
+            _ => Err(self.unexpected(Type::Function)),
+        }
+    }
+
+    /// Type of the value, looking through scripts to the value they return.
+    pub fn get_type(&self) -> Type {
+        match self {
+            Value::Number(_) => Type::Number,
+            Value::Bool(_) => Type::Bool,
+            Value::Text(_) => Type::Text,
+            Value::List(_) => Type::List,
+            Value::Point(_) => Type::Point,
+            Value::Line(_) => Type::Edge,
+            Value::Face(_) => Type::Face,
+            Value::Shape(_) => Type::Shape,
+            Value::Function(_) => Type::Function,
+            Value::Script(i) => i.value().get_type(),
+        }
+    }
+
+    pub fn unexpected(&self, expected: Type) -> RuntimeError {
+        RuntimeError::UnexpectedType {
+            expected,
+            actual: self.get_type(),
         }
     }
 
@@ -260,11 +317,39 @@ impl Value {
             Type::List => self.to_list().is_ok(),
             Type::Point => self.to_point().is_ok(),
             Type::Edge => self.to_line().is_ok(),
+            Type::Face => self.to_face().is_ok(),
             Type::Shape => self.to_shape().is_ok(),
             Type::Function => self.to_function().is_ok(),
         }
     }
 
+    /// Cost of converting the value to the target type, unwrapping a script or a single item
+    /// list is cheaper than changing the type so that overloads of the returned type win.
+    pub fn conversion_cost(&self, target: Type) -> usize {
+        match self {
+            _ if self.is_exact_type(target) => 0,
+            Value::Script(i) => 1 + i.value().conversion_cost(target),
+            Value::List(l) if l.len() == 1 => 1 + l[0].conversion_cost(target),
+            _ => CHANGE_TYPE_COST,
+        }
+    }
+
+    /// whether the value already has the target type and needs no conversion
+    fn is_exact_type(&self, target: Type) -> bool {
+        matches!(
+            (self, target),
+            (Value::Number(_), Type::Number)
+                | (Value::Bool(_), Type::Bool)
+                | (Value::Text(_), Type::Text)
+                | (Value::List(_), Type::List)
+                | (Value::Point(_), Type::Point)
+                | (Value::Line(_), Type::Edge)
+                | (Value::Face(_), Type::Face)
+                | (Value::Shape(_), Type::Shape)
+                | (Value::Function(_), Type::Function)
+        )
+    }
+
     pub fn to_type(&self, target: Type) -> Result<Value> {
         match target {
             Type::Number => Ok(self.to_number()?.into()),
@@ -273,21 +358,22 @@ impl Value {
             Type::List => Ok(self.to_list()?.into()),
             Type::Point => Ok(self.to_point()?.into()),
             Type::Edge => Ok(self.to_line()?.into()),
+            Type::Face => Ok(self.to_face()?.into()),
             Type::Shape => Ok(self.to_shape()?.into()),
             Type::Function => Ok(self.to_function()?.into()),
         }
     }
 
-    fn fuse_list<T: DsShape>(lines: &[Rc<T>]) -> Result<Rc<T>> {
+    fn fuse_list<T: DsShape>(&self, lines: &[Arc<T>], expected: Type) -> Result<Arc<T>> {
         match lines.len() {
-            0 => Err(RuntimeError::UnexpectedType()),
+            0 => Err(self.unexpected(expected)),
             1 => Ok(lines[0].clone()),
             _ => {
                 let mut acc = lines[0].fuse(lines[1].as_ref())?;
                 for value in &lines[2..] {
                     acc = acc.fuse(value.as_ref())?
                 }
-                Ok(Rc::new(acc))
+                Ok(Arc::new(acc))
             }
         }
     }
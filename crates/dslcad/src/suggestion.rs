@@ -0,0 +1,71 @@
+/// Closest candidate to `target` by edit distance, if any is close enough to be a likely typo.
+pub fn did_you_mean<'a>(
+    target: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let limit = (target.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != target)
+        .map(|candidate| (edit_distance(target, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Suffix for error messages suggesting `suggestion`.
+pub fn suggestion_message(suggestion: &Option<String>) -> String {
+    match suggestion {
+        Some(suggestion) => format!(", did you mean '{suggestion}'?"),
+        None => String::new(),
+    }
+}
+
+/// Edit distance counting insertions, deletions, substitutions and swaps of adjacent characters.
+fn edit_distance(left: &str, right: &str) -> usize {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+
+    let mut distances = vec![vec![0; right.len() + 1]; left.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    distances[0] = (0..=right.len()).collect();
+
+    for i in 1..=left.len() {
+        for j in 1..=right.len() {
+            let cost = usize::from(left[i - 1] != right[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[left.len()][right.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_measures_edit_distance() {
+        assert_eq!(0, edit_distance("cube", "cube"));
+        assert_eq!(1, edit_distance("cube", "cub"));
+        assert_eq!(1, edit_distance("cube", "cuve"));
+        assert_eq!(1, edit_distance("cube", "cueb"));
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+    }
+
+    #[test]
+    fn it_suggests_close_names() {
+        let names = ["cube", "cylinder", "sphere"];
+        assert_eq!(Some("cube".to_string()), did_you_mean("cueb", names));
+        assert_eq!(Some("sphere".to_string()), did_you_mean("shpere", names));
+        assert_eq!(None, did_you_mean("torus", names));
+    }
+}
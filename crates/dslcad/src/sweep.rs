@@ -0,0 +1,170 @@
+use crate::parser::{Expression, SweepValues};
+use std::collections::HashMap;
+
+/// Arguments where each parameter may take several values, evaluated once per combination.
+pub struct Sweep<'a> {
+    parameters: Vec<(&'a str, SweepValues)>,
+}
+
+/// One combination of a [Sweep].
+pub struct Variant<'a> {
+    /// Made from the values of the parameters that vary, such as `size_10-wall_1.5`. Empty when
+    /// no parameter varies.
+    pub name: String,
+    pub arguments: HashMap<&'a str, Expression>,
+}
+
+impl<'a> Sweep<'a> {
+    pub fn new(parameters: Vec<(&'a str, SweepValues)>) -> Self {
+        Self { parameters }
+    }
+
+    /// Add `arguments` for parameters that were not given, each with a single value.
+    pub fn with_defaults(
+        mut self,
+        arguments: impl IntoIterator<Item = (&'a str, Expression)>,
+    ) -> Self {
+        for (name, value) in arguments {
+            if !self
+                .parameters
+                .iter()
+                .any(|(parameter, _)| *parameter == name)
+            {
+                self.parameters.push((name, vec![(String::new(), value)]));
+            }
+        }
+        self
+    }
+
+    /// Number of combinations.
+    pub fn count(&self) -> usize {
+        self.parameters
+            .iter()
+            .map(|(_, values)| values.len())
+            .product()
+    }
+
+    /// Every combination of values, varying the last parameter fastest.
+    pub fn variants(&self) -> impl Iterator<Item = Variant<'a>> + '_ {
+        (0..self.count()).map(|mut index| {
+            let mut name = Vec::new();
+            let mut arguments = HashMap::new();
+            for (parameter, values) in self.parameters.iter().rev() {
+                let (label, value) = &values[index % values.len()];
+                index /= values.len();
+                if values.len() > 1 {
+                    name.push(format!("{parameter}_{}", file_name_part(label)));
+                }
+                arguments.insert(*parameter, value.clone());
+            }
+            name.reverse();
+
+            Variant {
+                name: name.join("-"),
+                arguments,
+            }
+        })
+    }
+}
+
+fn file_name_part(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Literal;
+
+    fn value(label: &str, literal: Literal) -> (String, Expression) {
+        (label.to_string(), Expression::Literal(literal, 0..0))
+    }
+
+    #[test]
+    fn it_lists_every_combination() {
+        let sweep = Sweep::new(vec![
+            (
+                "size",
+                vec![
+                    value("10", Literal::Number(10.)),
+                    value("20", Literal::Number(20.)),
+                ],
+            ),
+            (
+                "label",
+                vec![value("a b", Literal::Text("a b".to_string()))],
+            ),
+            (
+                "wall",
+                vec![
+                    value("1", Literal::Number(1.)),
+                    value("1.5", Literal::Number(1.5)),
+                ],
+            ),
+        ]);
+        assert_eq!(4, sweep.count());
+
+        let names: Vec<_> = sweep.variants().map(|variant| variant.name).collect();
+        assert_eq!(
+            vec![
+                "size_10-wall_1",
+                "size_10-wall_1.5",
+                "size_20-wall_1",
+                "size_20-wall_1.5"
+            ],
+            names
+        );
+
+        let last = sweep.variants().last().unwrap();
+        assert!(matches!(
+            last.arguments["size"],
+            Expression::Literal(Literal::Number(size), _) if size == 20.
+        ));
+        assert!(matches!(
+            &last.arguments["label"],
+            Expression::Literal(Literal::Text(label), _) if label == "a b"
+        ));
+        assert_eq!("point_x_1_", file_name_part("point(x=1)"));
+    }
+
+    #[test]
+    fn it_keeps_given_arguments_over_defaults() {
+        let sweep = Sweep::new(vec![(
+            "size",
+            vec![
+                value("10", Literal::Number(10.)),
+                value("20", Literal::Number(20.)),
+            ],
+        )])
+        .with_defaults(vec![
+            ("size", Expression::Literal(Literal::Number(30.), 0..0)),
+            ("wall", Expression::Literal(Literal::Number(2.), 0..0)),
+        ]);
+        assert_eq!(2, sweep.count());
+
+        let variants: Vec<_> = sweep.variants().collect();
+        assert_eq!("size_10", variants[0].name);
+        assert!(matches!(
+            variants[1].arguments["size"],
+            Expression::Literal(Literal::Number(size), _) if size == 20.
+        ));
+        assert!(matches!(
+            variants[1].arguments["wall"],
+            Expression::Literal(Literal::Number(wall), _) if wall == 2.
+        ));
+    }
+
+    #[test]
+    fn it_names_single_runs_nothing() {
+        let sweep = Sweep::new(vec![("size", vec![value("true", Literal::Bool(true))])]);
+        let variants: Vec<_> = sweep.variants().collect();
+        assert_eq!(1, variants.len());
+        assert_eq!("", variants[0].name);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::library::Library;
+use crate::parser::{DocId, ParseErrors, Parser, Reader};
+use crate::resources::ResourceExt;
+use crate::runtime::{Engine, RuntimeError, StackFrame, WithStack};
+use crate::ParseOptions;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// Files ending with this suffix are picked up by the test runner.
+pub const TEST_SUFFIX: &str = "_test.ds";
+
+/// Outcome of one `assert` statement.
+#[derive(Debug, Clone)]
+pub struct Assertion {
+    pub frame: StackFrame,
+    pub message: String,
+    pub passed: bool,
+}
+
+/// Collects every assertion instead of stopping at the first one that fails.
+#[derive(Default)]
+pub struct Assertions {
+    results: Mutex<Vec<Assertion>>,
+}
+
+impl Assertions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn record(&self, assertion: Assertion) {
+        self.results.lock().unwrap().push(assertion);
+    }
+
+    /// Assertions in the order they appear in their documents.
+    pub fn results(&self) -> Vec<Assertion> {
+        let mut results = self.results.lock().unwrap().clone();
+        results.sort_by(|a, b| {
+            (a.frame.document.to_str(), a.frame.span.start)
+                .cmp(&(b.frame.document.to_str(), b.frame.span.start))
+        });
+        results
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TestError {
+    #[error(transparent)]
+    Parse(#[from] ParseErrors),
+    #[error(transparent)]
+    Runtime(#[from] WithStack<RuntimeError>),
+}
+
+/// Result of running a single test file.
+#[derive(Debug)]
+pub struct TestFile {
+    pub path: PathBuf,
+    pub assertions: Vec<Assertion>,
+    pub error: Option<TestError>,
+}
+
+impl TestFile {
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.assertions.iter().all(|a| a.passed)
+    }
+}
+
+/// Every test file in `path` and its subdirectories, or `path` itself when it is a file.
+pub fn discover(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut found = Vec::new();
+    for entry in std::fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            found.extend(discover(&path)?);
+        } else if path.to_string_lossy().ends_with(TEST_SUFFIX) {
+            found.push(path);
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Evaluate the test file at `path`, recording every assertion it makes.
+///
+/// Test files do not have to draw anything, a script without a return value still passes.
+pub fn run_test<R: Reader>(reader: R, path: &Path, options: &ParseOptions) -> TestFile {
+    let document = DocId::new(path.to_string_lossy().to_string());
+    let assertions = Assertions::new();
+    let parser = Parser::new(reader, document)
+        .with_unit(options.unit)
+        .with_library_paths(options.library_paths.clone())
+        .with_default_loaders();
+    let error = match parser.parse() {
+        Ok(ast) => {
+            let library = Library::default();
+            let mut engine = Engine::new(&library, &ast).with_assertions(&assertions);
+            match engine.eval_root(HashMap::new()) {
+                Err(WithStack {
+                    error: RuntimeError::NoReturnValue(),
+                    stack,
+                }) if stack.is_empty() => None,
+                Err(e) => Some(e.into()),
+                Ok(_) => None,
+            }
+        }
+        Err(e) => Some(e.into()),
+    };
+
+    TestFile {
+        path: path.to_path_buf(),
+        assertions: assertions.results(),
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::Cache;
+    use crate::tests::TestReader;
+
+    #[test]
+    fn it_records_assertions_with_a_cache() {
+        let (library, cache) = (Library::default(), Cache::new());
+        let parser = Parser::new(
+            TestReader("var a = 5; assert a > 1;"),
+            DocId::new("a".into()),
+        );
+        let ast = parser.parse().unwrap();
+        for _ in 0..2 {
+            let assertions = Assertions::new();
+            let mut engine = Engine::new(&library, &ast)
+                .with_cache(&cache)
+                .with_assertions(&assertions);
+            engine.eval_root(HashMap::new()).unwrap_err();
+            assert_eq!(1, assertions.results().len());
+        }
+    }
+
+    #[test]
+    fn it_reports_every_assertion() {
+        let code = "var a = 5;
+            assert a > 1, \"a is big enough\";
+            assert a < 2, \"a is small enough\";
+            assert approx(left=a, right=5.0001, tolerance=0.001);";
+        let result = run_test(
+            TestReader(code),
+            Path::new("a_test.ds"),
+            &ParseOptions::default(),
+        );
+
+        assert!(result.error.is_none());
+        assert!(!result.passed());
+        let outcomes: Vec<_> = result
+            .assertions
+            .iter()
+            .map(|a| (a.message.as_str(), a.passed))
+            .collect();
+        assert_eq!(
+            vec![
+                ("a is big enough", true),
+                ("a is small enough", false),
+                ("approx(left=a, right=5.0001, tolerance=0.001)", true),
+            ],
+            outcomes
+        );
+    }
+
+    #[test]
+    fn it_reports_errors() {
+        let result = run_test(
+            TestReader("assert missing;"),
+            Path::new("a_test.ds"),
+            &ParseOptions::default(),
+        );
+        assert!(matches!(result.error, Some(TestError::Parse(_))));
+
+        let result = run_test(
+            TestReader("assert 5;"),
+            Path::new("a_test.ds"),
+            &ParseOptions::default(),
+        );
+        assert!(matches!(result.error, Some(TestError::Runtime(_))));
+        assert!(!result.passed());
+    }
+}
@@ -1,8 +1,15 @@
 use dslcad::{eval, parse, render};
+use dslcad_storage::protocol::{Render, Snapshot};
 use std::collections::HashMap;
+use std::path::Path;
 use std::time::Instant;
 use walkdir::WalkDir;
 
+/// Largest change in a snapshot value, relative to its size, that is not reported.
+const SNAPSHOT_TOLERANCE: f64 = 0.001;
+/// Set to rewrite snapshots that no longer match instead of failing.
+const UPDATE_SNAPSHOTS: &str = "DSLCAD_UPDATE_SNAPSHOTS";
+
 #[test]
 fn can_run_examples() {
     for file in WalkDir::new("../../examples")
@@ -24,11 +31,54 @@ fn run_example(path: &str) {
 
     println!("\tParsed in {:.4}ms", now.elapsed().as_secs_f64() * 1000.0);
 
-    render(eval(ast, HashMap::new()).unwrap(), 0.1)
+    let render = render(eval(ast, HashMap::new()).unwrap(), 0.1)
         .unwrap_or_else(|e| panic!("failed to render {}\n{e}", &path));
 
     println!(
         "\tRendered in {:.4}ms",
         now.elapsed().as_secs_f64() * 1000.0
     );
+
+    check_snapshot(path, &render);
+}
+
+/// Compare the geometry of an example with the snapshot stored next to it.
+fn check_snapshot(path: &str, render: &Render) {
+    let snapshot_path = Path::new(path).with_extension("snapshot.json");
+    let snapshot = render.snapshot();
+    let update = std::env::var_os(UPDATE_SNAPSHOTS).is_some();
+
+    let Ok(json) = std::fs::read_to_string(&snapshot_path) else {
+        assert!(
+            update,
+            "{path} has no snapshot, run with {UPDATE_SNAPSHOTS}=1 to record one"
+        );
+        write_snapshot(&snapshot_path, &snapshot);
+        return;
+    };
+
+    let expected: Snapshot = serde_json::from_str(&json).expect("invalid snapshot");
+    let differences = snapshot.differences(&expected, SNAPSHOT_TOLERANCE);
+    if !differences.is_empty() && update {
+        write_snapshot(&snapshot_path, &snapshot);
+        return;
+    }
+
+    assert!(
+        differences.is_empty(),
+        "geometry of {path} changed:\n{}\nrun with {UPDATE_SNAPSHOTS}=1 to accept the changes",
+        differences.join("\n")
+    );
+
+    for (i, (actual, expected)) in snapshot.parts.iter().zip(&expected.parts).enumerate() {
+        if actual.triangles != expected.triangles || actual.mesh_hash != expected.mesh_hash {
+            println!("\tPart {i} is meshed differently, its measurements still match");
+        }
+    }
+}
+
+fn write_snapshot(path: &Path, snapshot: &Snapshot) {
+    let json = serde_json::to_string_pretty(snapshot).unwrap();
+    std::fs::write(path, json + "\n").unwrap();
+    println!("\tSnapshot written to {}", path.to_string_lossy());
 }
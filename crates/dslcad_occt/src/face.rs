@@ -0,0 +1,180 @@
+use crate::command::Builder;
+use crate::explorer::Explorer;
+use crate::shape::triangulate;
+use crate::shapes::detached;
+use crate::{DsShape, Error, JoinType, Mesh, Point, Wire};
+use cxx::UniquePtr;
+use opencascade_sys::ffi::{
+    cast_face_to_shape, cast_wire_to_shape, BRepBuilderAPI_MakeFace_wire,
+    BRepGProp_SurfaceProperties, BRep_Tool_Pnt, GProp_GProps_CentreOfMass, GProp_GProps_ctor,
+    TopoDS_Edge, TopoDS_Face, TopoDS_Shape, TopoDS_Shape_to_owned, TopoDS_Wire,
+    TopoDS_cast_to_wire,
+};
+
+/// Planar region bounded by one or more closed wires, possibly made of several disjoint faces.
+pub struct Face(pub(crate) UniquePtr<TopoDS_Shape>);
+
+// SAFETY: like `Shape`, faces are never written once built.
+unsafe impl Send for Face {}
+unsafe impl Sync for Face {}
+
+impl AsRef<TopoDS_Shape> for Face {
+    fn as_ref(&self) -> &TopoDS_Shape {
+        &self.0
+    }
+}
+
+impl DsShape for Face {
+    fn shape(&self) -> &TopoDS_Shape {
+        &self.0
+    }
+}
+
+impl Face {
+    pub fn from_wire(wire: &Wire) -> Result<Self, Error> {
+        let wire = detached(wire.shape())?;
+        let mut face_builder = BRepBuilderAPI_MakeFace_wire(TopoDS_cast_to_wire(&wire), false);
+        Ok(Builder::try_build(&mut face_builder)?.into())
+    }
+
+    pub fn new(outer: &Wire, holes: &[&Wire]) -> Result<Self, Error> {
+        let mut face = Self::from_wire(outer)?;
+        for hole in holes {
+            face = face.cut(&Self::from_wire(hole)?)?;
+        }
+        Ok(face)
+    }
+
+    pub fn wires(&self) -> Vec<Wire> {
+        let mut wires = Vec::new();
+
+        let mut wire_explorer: Explorer<TopoDS_Wire> = Explorer::new(self);
+        while let Some(wire) = wire_explorer.next() {
+            wires.push(cast_wire_to_shape(wire).into());
+        }
+
+        wires
+    }
+
+    pub fn offset(&self, distance: f64, join: JoinType) -> Result<Self, Error> {
+        let mut result: Option<Face> = None;
+
+        let mut face_explorer: Explorer<TopoDS_Face> = Explorer::new(self);
+        while let Some(face) = face_explorer.next() {
+            let face: Face = cast_face_to_shape(face).into();
+
+            // the outer boundary encloses the largest area, every other wire is a hole
+            let mut wires = face.wires();
+            let mut outer = 0;
+            let mut largest = 0.;
+            for (i, wire) in wires.iter().enumerate() {
+                let area = Self::from_wire(wire)?.area();
+                if area > largest {
+                    largest = area;
+                    outer = i;
+                }
+            }
+            let outer = wires.remove(outer).offset(distance, join)?;
+            let holes = wires
+                .iter()
+                .map(|hole| hole.offset(-distance, join))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let offset = Self::new(&outer, &holes.iter().collect::<Vec<_>>())?;
+            result = Some(match result {
+                Some(acc) => acc.fuse(&offset)?,
+                None => offset,
+            });
+        }
+
+        result.ok_or_else(|| "face has no geometry to offset".into())
+    }
+
+    pub fn mesh(&self, deflection: f64) -> Result<Mesh, Error> {
+        triangulate(&self.0, deflection)
+    }
+
+    pub fn center_of_mass(&self) -> Point {
+        let mut props = GProp_GProps_ctor();
+        BRepGProp_SurfaceProperties(self.shape(), props.pin_mut());
+        GProp_GProps_CentreOfMass(&props).into()
+    }
+
+    pub fn area(&self) -> f64 {
+        let mut props = GProp_GProps_ctor();
+        BRepGProp_SurfaceProperties(self.shape(), props.pin_mut());
+        props.Mass()
+    }
+
+    pub fn lines(&self, deflection: f64) -> Result<Vec<Vec<[f64; 3]>>, Error> {
+        let mut lines = Vec::new();
+
+        let mut edge_explorer: Explorer<TopoDS_Edge> = Explorer::new(self);
+        while let Some(edge) = edge_explorer.next() {
+            if let Some(line) = Wire::extract_line(edge, deflection) {
+                lines.push(line);
+            }
+        }
+
+        Ok(lines)
+    }
+
+    pub fn points(&self) -> Result<Vec<[f64; 3]>, Error> {
+        let mut points = Vec::new();
+
+        let mut vertex_explorer = Explorer::new(self);
+        while let Some(vertex) = vertex_explorer.next() {
+            let point: Point = BRep_Tool_Pnt(vertex).into();
+            points.push(point.into());
+        }
+
+        Ok(points)
+    }
+}
+
+impl From<&TopoDS_Shape> for Face {
+    fn from(value: &TopoDS_Shape) -> Self {
+        Face(TopoDS_Shape_to_owned(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Edge, WireFactory};
+
+    fn square(size: f64) -> Face {
+        let corners = [
+            Point::new(0., 0., 0.),
+            Point::new(0., size, 0.),
+            Point::new(size, size, 0.),
+            Point::new(size, 0., 0.),
+        ];
+        let mut wire = WireFactory::new();
+        for i in 0..corners.len() {
+            let next = (i + 1) % corners.len();
+            wire.add_edge(&Edge::new_line(&corners[i], &corners[next]).unwrap());
+        }
+        Face::from_wire(&wire.build().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn it_can_cut_faces() {
+        let outer = square(10.);
+        let inner = square(5.).translate(&Point::new(2.5, 2.5, 0.)).unwrap();
+
+        let face = outer.cut(&inner).unwrap();
+        assert!((face.area() - 75.).abs() < 0.001);
+    }
+
+    #[test]
+    fn it_can_offset_faces_with_holes() {
+        let outer = square(10.);
+        let inner = square(5.).translate(&Point::new(2.5, 2.5, 0.)).unwrap();
+        let face = outer.cut(&inner).unwrap();
+
+        let grown = face.offset(1., JoinType::Arc).unwrap();
+        assert!(grown.area() > face.area());
+        assert_eq!(2, grown.wires().len());
+    }
+}
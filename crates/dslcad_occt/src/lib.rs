@@ -3,6 +3,7 @@ mod compound;
 mod edge;
 mod error;
 mod explorer;
+mod face;
 mod mesh;
 mod point;
 mod shape;
@@ -12,6 +13,7 @@ mod wire;
 
 pub use edge::*;
 pub use error::*;
+pub use face::*;
 pub use mesh::*;
 pub use point::*;
 pub use shape::*;
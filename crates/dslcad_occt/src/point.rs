@@ -7,7 +7,10 @@ pub struct Point {
     pub(crate) point: UniquePtr<gp_Pnt>,
 }
 
+// SAFETY: a `gp_Pnt` is three coordinates owned by this point alone, it is only written while
+// being built and never shares state with other OCCT objects.
 unsafe impl Send for Point {}
+unsafe impl Sync for Point {}
 
 impl Point {
     pub fn new_2d(x: f64, y: f64) -> Self {
@@ -1,29 +1,41 @@
 use crate::command::Builder;
 use crate::explorer::Explorer;
-use crate::shapes::DsShape;
-use crate::{Error, Mesh, Point, Wire};
+use crate::shapes::{detached, DsShape};
+use crate::{Error, Face, Mesh, Point, Wire};
 use cxx::UniquePtr;
 use log::debug;
 use opencascade_sys::ffi::{
     gp_Ax2_ctor, gp_DZ, gp_OX, gp_OY, gp_OZ, new_vec, BRepAlgoAPI_Common, BRepAlgoAPI_Cut,
-    BRepAlgoAPI_Fuse, BRepAlgoAPI_Section, BRepBuilderAPI_GTransform, BRepBuilderAPI_MakeFace,
-    BRepBuilderAPI_MakeFace_wire, BRepBuilderAPI_Transform, BRepFilletAPI_MakeChamfer,
+    BRepAlgoAPI_Fuse, BRepAlgoAPI_Section, BRepBndLib_AddOptimal, BRepBuilderAPI_GTransform,
+    BRepBuilderAPI_MakeFace, BRepBuilderAPI_Transform, BRepFilletAPI_MakeChamfer,
     BRepFilletAPI_MakeChamfer_ctor, BRepFilletAPI_MakeFillet, BRepFilletAPI_MakeFillet_ctor,
-    BRepGProp_VolumeProperties, BRepMesh_IncrementalMesh_ctor, BRepPrimAPI_MakeBox,
-    BRepPrimAPI_MakeBox_ctor, BRepPrimAPI_MakeCylinder, BRepPrimAPI_MakeCylinder_ctor,
-    BRepPrimAPI_MakePrism, BRepPrimAPI_MakePrism_ctor, BRepPrimAPI_MakeRevol,
-    BRepPrimAPI_MakeRevol_ctor, BRepPrimAPI_MakeSphere, BRepPrimAPI_MakeSphere_ctor, BRep_Tool_Pnt,
-    BRep_Tool_Triangulation, GProp_GProps_CentreOfMass, GProp_GProps_ctor,
-    HandlePoly_Triangulation_Get, Poly_Triangulation_Node, TopAbs_Orientation, TopAbs_ShapeEnum,
-    TopExp_Explorer_ctor, TopLoc_Location_ctor, TopoDS_Edge, TopoDS_Shape, TopoDS_Shape_to_owned,
-    TopoDS_cast_to_face,
+    BRepGProp_VolumeProperties, BRepMesh_IncrementalMesh_ctor, BRepOffsetAPI_MakeOffsetShape,
+    BRepOffsetAPI_MakeOffsetShape_ctor, BRepOffsetAPI_ThruSections,
+    BRepOffsetAPI_ThruSections_ctor, BRepPrimAPI_MakeBox, BRepPrimAPI_MakeBox_ctor,
+    BRepPrimAPI_MakeCylinder, BRepPrimAPI_MakeCylinder_ctor, BRepPrimAPI_MakePrism,
+    BRepPrimAPI_MakePrism_ctor, BRepPrimAPI_MakeRevol, BRepPrimAPI_MakeRevol_ctor,
+    BRepPrimAPI_MakeSphere, BRepPrimAPI_MakeSphere_ctor, BRep_Tool_Pnt, BRep_Tool_Triangulation,
+    Bnd_Box_CornerMax, Bnd_Box_CornerMin, Bnd_Box_ctor, GProp_GProps_CentreOfMass,
+    GProp_GProps_ctor, HandlePoly_Triangulation_Get, Poly_Triangulation_Node, TopAbs_Orientation,
+    TopAbs_ShapeEnum, TopExp_Explorer_ctor, TopLoc_Location_ctor, TopoDS_Edge, TopoDS_Shape,
+    TopoDS_Shape_to_owned, TopoDS_cast_to_face, TopoDS_cast_to_wire,
 };
 use std::f64::consts::PI;
 
+// tolerance used to find coincident faces while offsetting solids
+const OFFSET_TOLERANCE: f64 = 1e-6;
+
 pub struct Shape {
     pub(crate) shape: UniquePtr<TopoDS_Shape>,
 }
 
+// SAFETY: shapes share their `TopoDS_TShape`s with the shapes they were built from, but those are
+// never written once built because OCCT builders are only handed `detached` copies. Reading OCCT
+// topology and geometry from several threads at once is safe, and handles to it are reference
+// counted atomically, so shapes can be dropped, sent and read anywhere.
+unsafe impl Send for Shape {}
+unsafe impl Sync for Shape {}
+
 impl AsRef<TopoDS_Shape> for Shape {
     fn as_ref(&self) -> &TopoDS_Shape {
         &self.shape
@@ -63,21 +75,34 @@ impl Shape {
     }
 
     pub fn extrude(wire: &Wire, x: f64, y: f64, z: f64) -> Result<Self, Error> {
-        let mut face_profile = BRepBuilderAPI_MakeFace_wire(wire.wire(), false);
+        Self::extrude_face(&Face::from_wire(wire)?, x, y, z)
+    }
+
+    pub fn extrude_face(face: &Face, x: f64, y: f64, z: f64) -> Result<Self, Error> {
         let prism_vec = new_vec(x, y, z);
 
-        let mut body = BRepPrimAPI_MakePrism_ctor(
-            Builder::try_build(&mut face_profile)?,
-            &prism_vec,
-            true,
-            true,
-        );
+        let mut body = BRepPrimAPI_MakePrism_ctor(&detached(face.shape())?, &prism_vec, true, true);
         Ok(Builder::try_build(&mut body)?.into())
     }
 
+    pub fn loft(sections: &[Wire]) -> Result<Self, Error> {
+        if sections.len() < 2 {
+            return Err("loft requires at least two sections".into());
+        }
+
+        let mut loft = BRepOffsetAPI_ThruSections_ctor(true);
+        for section in sections {
+            loft.pin_mut()
+                .AddWire(TopoDS_cast_to_wire(&detached(section.shape())?));
+        }
+        Ok(Builder::try_build(&mut loft)?.into())
+    }
+
     pub fn extrude_rotate(wire: &Wire, axis: Axis, degrees: f64) -> Result<Self, Error> {
-        let mut face_profile = BRepBuilderAPI_MakeFace_wire(wire.wire(), false);
+        Self::extrude_rotate_face(&Face::from_wire(wire)?, axis, degrees)
+    }
 
+    pub fn extrude_rotate_face(face: &Face, axis: Axis, degrees: f64) -> Result<Self, Error> {
         let radians = degrees * (std::f64::consts::PI / 180.);
         let gp_axis = match axis {
             Axis::X => gp_OX(),
@@ -85,19 +110,18 @@ impl Shape {
             Axis::Z => gp_OZ(),
         };
 
-        let mut body = BRepPrimAPI_MakeRevol_ctor(
-            Builder::try_build(&mut face_profile)?,
-            gp_axis,
-            radians,
-            true,
-        );
+        let mut body = BRepPrimAPI_MakeRevol_ctor(&detached(face.shape())?, gp_axis, radians, true);
         Ok(Builder::try_build(&mut body)?.into())
     }
 
     pub fn fillet(target: &Shape, thickness: f64) -> Result<Self, Error> {
+        // the edges have to come from the copy the fillet is built on
+        let target = Shape {
+            shape: detached(&target.shape)?,
+        };
         let mut fillet = BRepFilletAPI_MakeFillet_ctor(&target.shape);
 
-        let mut edge_explorer: Explorer<TopoDS_Edge> = Explorer::new(target);
+        let mut edge_explorer: Explorer<TopoDS_Edge> = Explorer::new(&target);
         while let Some(edge) = edge_explorer.next() {
             fillet.pin_mut().add_edge(thickness, edge);
         }
@@ -106,9 +130,12 @@ impl Shape {
     }
 
     pub fn chamfer(target: &Shape, thickness: f64) -> Result<Self, Error> {
+        let target = Shape {
+            shape: detached(&target.shape)?,
+        };
         let mut chamfer = BRepFilletAPI_MakeChamfer_ctor(&target.shape);
 
-        let mut edge_explorer: Explorer<TopoDS_Edge> = Explorer::new(target);
+        let mut edge_explorer: Explorer<TopoDS_Edge> = Explorer::new(&target);
         while let Some(edge) = edge_explorer.next() {
             chamfer.pin_mut().add_edge(thickness, edge);
         }
@@ -116,6 +143,18 @@ impl Shape {
         Ok(Builder::try_build(&mut chamfer)?.into())
     }
 
+    pub fn offset(&self, distance: f64) -> Result<Self, Error> {
+        let mut offset =
+            BRepOffsetAPI_MakeOffsetShape_ctor(&detached(&self.shape)?, distance, OFFSET_TOLERANCE);
+        let shape: Shape = Builder::try_build(&mut offset)?.into();
+
+        // shrinking past the middle of the shape leaves nothing, or a solid turned inside out
+        if distance < 0. && !(0. < shape.volume() && shape.volume() < self.volume()) {
+            return Err("offset shrinks the shape away entirely".into());
+        }
+        Ok(shape)
+    }
+
     pub fn center_of_mass(&self) -> Point {
         let mut props = GProp_GProps_ctor();
         BRepGProp_VolumeProperties(self.shape(), props.pin_mut());
@@ -128,52 +167,22 @@ impl Shape {
         props.Mass()
     }
 
-    pub fn mesh(&self, deflection: f64) -> Result<Mesh, Error> {
-        let mut incremental_mesh = BRepMesh_IncrementalMesh_ctor(&self.shape, deflection);
-        if !incremental_mesh.IsDone() {
-            return Err("unable to build incremental mesh".into());
-        }
-
-        let mut mesh = Mesh::default();
-
-        let mut edge_explorer = TopExp_Explorer_ctor(
-            incremental_mesh.pin_mut().Shape(),
-            TopAbs_ShapeEnum::TopAbs_FACE,
-        );
-        while edge_explorer.More() {
-            let face = TopoDS_cast_to_face(edge_explorer.Current());
-            let mut location = TopLoc_Location_ctor();
-
-            let triangulation_handle = BRep_Tool_Triangulation(face, location.pin_mut());
-            if let Ok(triangulation) = HandlePoly_Triangulation_Get(&triangulation_handle) {
-                let index_offset = mesh.vertices.len();
-                for index in 1..=triangulation.NbNodes() {
-                    let node = Poly_Triangulation_Node(triangulation, index);
-                    mesh.vertices.push([node.X(), node.Y(), node.Z()]);
-                }
-
-                for index in 1..=triangulation.NbTriangles() {
-                    let triangle = triangulation.Triangle(index);
-                    if face.Orientation() == TopAbs_Orientation::TopAbs_FORWARD {
-                        mesh.triangles.push([
-                            index_offset + triangle.Value(1) as usize - 1,
-                            index_offset + triangle.Value(2) as usize - 1,
-                            index_offset + triangle.Value(3) as usize - 1,
-                        ]);
-                    } else {
-                        mesh.triangles.push([
-                            index_offset + triangle.Value(3) as usize - 1,
-                            index_offset + triangle.Value(2) as usize - 1,
-                            index_offset + triangle.Value(1) as usize - 1,
-                        ]);
-                    }
-                }
-            }
-
-            edge_explorer.pin_mut().Next();
+    /// Corners of the axis aligned box around the shape, measured on its exact geometry so it
+    /// neither meshes the shape nor depends on a deflection.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let mut bounds = Bnd_Box_ctor();
+        BRepBndLib_AddOptimal(self.shape(), bounds.pin_mut(), false, false);
+        if bounds.IsVoid() {
+            return None;
         }
+        Some((
+            Bnd_Box_CornerMin(&bounds).into(),
+            Bnd_Box_CornerMax(&bounds).into(),
+        ))
+    }
 
-        Ok(mesh)
+    pub fn mesh(&self, deflection: f64) -> Result<Mesh, Error> {
+        triangulate(&self.shape, deflection)
     }
 
     pub fn lines(&self, deflection: f64) -> Result<Vec<Vec<[f64; 3]>>, Error> {
@@ -210,6 +219,56 @@ impl Shape {
     }
 }
 
+pub(crate) fn triangulate(shape: &TopoDS_Shape, deflection: f64) -> Result<Mesh, Error> {
+    // meshing stores the triangulation on the faces of the shape it is given
+    let shape = detached(shape)?;
+    let mut incremental_mesh = BRepMesh_IncrementalMesh_ctor(&shape, deflection);
+    if !incremental_mesh.IsDone() {
+        return Err("unable to build incremental mesh".into());
+    }
+
+    let mut mesh = Mesh::default();
+
+    let mut edge_explorer = TopExp_Explorer_ctor(
+        incremental_mesh.pin_mut().Shape(),
+        TopAbs_ShapeEnum::TopAbs_FACE,
+    );
+    while edge_explorer.More() {
+        let face = TopoDS_cast_to_face(edge_explorer.Current());
+        let mut location = TopLoc_Location_ctor();
+
+        let triangulation_handle = BRep_Tool_Triangulation(face, location.pin_mut());
+        if let Ok(triangulation) = HandlePoly_Triangulation_Get(&triangulation_handle) {
+            let index_offset = mesh.vertices.len();
+            for index in 1..=triangulation.NbNodes() {
+                let node = Poly_Triangulation_Node(triangulation, index);
+                mesh.vertices.push([node.X(), node.Y(), node.Z()]);
+            }
+
+            for index in 1..=triangulation.NbTriangles() {
+                let triangle = triangulation.Triangle(index);
+                if face.Orientation() == TopAbs_Orientation::TopAbs_FORWARD {
+                    mesh.triangles.push([
+                        index_offset + triangle.Value(1) as usize - 1,
+                        index_offset + triangle.Value(2) as usize - 1,
+                        index_offset + triangle.Value(3) as usize - 1,
+                    ]);
+                } else {
+                    mesh.triangles.push([
+                        index_offset + triangle.Value(3) as usize - 1,
+                        index_offset + triangle.Value(2) as usize - 1,
+                        index_offset + triangle.Value(1) as usize - 1,
+                    ]);
+                }
+            }
+        }
+
+        edge_explorer.pin_mut().Next();
+    }
+
+    Ok(mesh)
+}
+
 impl From<&TopoDS_Shape> for Shape {
     fn from(value: &TopoDS_Shape) -> Self {
         Shape {
@@ -260,10 +319,13 @@ shape_builder!(BRepAlgoAPI_Section);
 shape_builder!(BRepBuilderAPI_Transform);
 shape_builder!(BRepBuilderAPI_MakeFace);
 shape_builder!(BRepBuilderAPI_GTransform);
+shape_builder!(BRepOffsetAPI_ThruSections);
+shape_builder!(BRepOffsetAPI_MakeOffsetShape);
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Edge, WireFactory};
 
     #[test]
     fn it_can_write_box_stl() {
@@ -297,12 +359,78 @@ mod tests {
         shape.mesh(0.1).unwrap();
     }
 
+    #[test]
+    fn it_can_offset_box_stl() {
+        let b = Shape::cube(10., 10., 10.).unwrap();
+        let shape = b.offset(1.).unwrap();
+        assert!(shape.volume() > b.volume());
+        shape.mesh(0.1).unwrap();
+    }
+
+    #[test]
+    fn it_can_shrink_shapes() {
+        let b = Shape::cube(10., 10., 10.).unwrap();
+        let shape = b.offset(-1.).unwrap();
+        assert!((shape.volume() - 8. * 8. * 8.).abs() < 1e-3);
+    }
+
+    #[test]
+    fn it_rejects_shrinking_shapes_away() {
+        let b = Shape::cube(10., 10., 10.).unwrap();
+        assert!(b.offset(-6.).is_err());
+    }
+
+    #[test]
+    fn it_can_share_shapes_between_threads() {
+        let cube = Shape::cube(10., 10., 10.).unwrap();
+        std::thread::scope(|scope| {
+            for i in 0..4 {
+                let cube = &cube;
+                scope.spawn(move || {
+                    let sphere = Shape::sphere(2.).unwrap();
+                    let sphere = sphere.translate(&Point::new(i as f64, 0., 0.)).unwrap();
+                    cube.cut(&sphere).unwrap().mesh(0.1).unwrap();
+                    cube.mesh(0.1).unwrap();
+                });
+            }
+        });
+        assert!((cube.volume() - 1000.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn it_measures_exact_bounds() {
+        let (min, max) = Shape::sphere(2.).unwrap().bounds().unwrap();
+        assert!(min.distance(&Point::new(-2., -2., -2.)) < 1e-6);
+        assert!(max.distance(&Point::new(2., 2., 2.)) < 1e-6);
+    }
+
     #[test]
     fn it_can_write_cylinder_stl() {
         let shape = Shape::cylinder(10., 100.).unwrap();
         shape.mesh(0.1).unwrap();
     }
 
+    #[test]
+    fn it_can_loft_stl() {
+        let square = |size: f64, z: f64| {
+            let corners = [
+                Point::new(0., 0., z),
+                Point::new(0., size, z),
+                Point::new(size, size, z),
+                Point::new(size, 0., z),
+            ];
+            let mut wire = WireFactory::new();
+            for i in 0..corners.len() {
+                let next = (i + 1) % corners.len();
+                wire.add_edge(&Edge::new_line(&corners[i], &corners[next]).unwrap());
+            }
+            wire.build().unwrap()
+        };
+
+        let shape = Shape::loft(&[square(10., 0.), square(5., 10.)]).unwrap();
+        shape.mesh(0.1).unwrap();
+    }
+
     #[test]
     fn it_can_write_translated_stl() {
         let b = Shape::cube(10., 10., 10.).unwrap();
@@ -1,12 +1,27 @@
 use crate::command::Builder;
 use crate::compound::Compound;
 use crate::{Axis, Error, Point, Wire};
+use cxx::UniquePtr;
 use opencascade_sys::ffi::{
     gp_OX, gp_OY, gp_OZ, new_gp_GTrsf, new_transform, BRepAlgoAPI_Common_ctor,
     BRepAlgoAPI_Cut_ctor, BRepAlgoAPI_Fuse_ctor, BRepAlgoAPI_Section_ctor,
     BRepBuilderAPI_GTransform_ctor, BRepBuilderAPI_MakeFace_wire, BRepBuilderAPI_Transform_ctor,
-    TopoDS_Shape, TopoDS_cast_to_compound,
+    BRepTools_Read, BRepTools_Write, TopoDS_Shape, TopoDS_Shape_ctor, TopoDS_Shape_to_owned,
+    TopoDS_cast_to_compound, TopoDS_cast_to_wire,
 };
+use std::path::Path;
+
+/// Deep copy of `shape` that shares no sub-shapes with it.
+///
+/// OCCT builders write into the shapes they are given: they add pcurves to edges, widen the
+/// tolerances of edges and vertices and store triangulations on faces. Built shapes are shared
+/// between values and threads, so builders in this crate are only ever handed detached copies
+/// and a shape is never written once it is built.
+pub(crate) fn detached(shape: &TopoDS_Shape) -> Result<UniquePtr<TopoDS_Shape>, Error> {
+    // copying through the transform rebuilds every sub-shape instead of moving its location
+    let mut copy = BRepBuilderAPI_Transform_ctor(shape, &new_transform(), true);
+    Ok(TopoDS_Shape_to_owned(Builder::try_build(&mut copy)?))
+}
 
 pub trait DsShape: for<'a> From<&'a TopoDS_Shape> {
     fn shape(&self) -> &TopoDS_Shape;
@@ -91,30 +106,54 @@ pub trait DsShape: for<'a> From<&'a TopoDS_Shape> {
     }
 
     fn fuse(&self, right: &Self) -> Result<Self, Error> {
-        Ok(Builder::try_build(&mut BRepAlgoAPI_Fuse_ctor(self.shape(), right.shape()))?.into())
+        let (left, right) = (detached(self.shape())?, detached(right.shape())?);
+        Ok(Builder::try_build(&mut BRepAlgoAPI_Fuse_ctor(&left, &right))?.into())
     }
 
     fn cut(&self, right: &Self) -> Result<Self, Error> {
-        Ok(Builder::try_build(&mut BRepAlgoAPI_Cut_ctor(self.shape(), right.shape()))?.into())
+        let (left, right) = (detached(self.shape())?, detached(right.shape())?);
+        Ok(Builder::try_build(&mut BRepAlgoAPI_Cut_ctor(&left, &right))?.into())
     }
 
     fn intersect(&self, right: &Self) -> Result<Self, Error> {
-        Ok(Builder::try_build(&mut BRepAlgoAPI_Common_ctor(self.shape(), right.shape()))?.into())
+        let (left, right) = (detached(self.shape())?, detached(right.shape())?);
+        Ok(Builder::try_build(&mut BRepAlgoAPI_Common_ctor(&left, &right))?.into())
     }
 
     fn section_2d(&self, right: &Wire) -> Result<Wire, Error> {
-        let mut face_builder = BRepBuilderAPI_MakeFace_wire(right.wire(), false);
+        let wire = detached(right.shape())?;
+        let mut face_builder = BRepBuilderAPI_MakeFace_wire(TopoDS_cast_to_wire(&wire), false);
         let face = Builder::try_build(&mut face_builder)?;
-        let binding = &mut BRepAlgoAPI_Section_ctor(self.shape(), face);
+        let binding = &mut BRepAlgoAPI_Section_ctor(&detached(self.shape())?, face);
         let compound_shape: Compound = TopoDS_cast_to_compound(Builder::try_build(binding)?).into();
         compound_shape.try_into()
     }
 
     fn section(&self, right: &Self) -> Result<Wire, Error> {
-        let binding = &mut BRepAlgoAPI_Section_ctor(self.shape(), right.shape());
+        let (left, right) = (detached(self.shape())?, detached(right.shape())?);
+        let binding = &mut BRepAlgoAPI_Section_ctor(&left, &right);
         let compound_shape: Compound = TopoDS_cast_to_compound(Builder::try_build(binding)?).into();
         compound_shape.try_into()
     }
+
+    fn write_brep(&self, path: &Path) -> Result<(), Error> {
+        let path = path.to_str().ok_or("brep path must be valid unicode")?;
+        if BRepTools_Write(self.shape(), path) {
+            Ok(())
+        } else {
+            Err(format!("could not write brep to {path}").into())
+        }
+    }
+
+    fn read_brep(path: &Path) -> Result<Self, Error> {
+        let path = path.to_str().ok_or("brep path must be valid unicode")?;
+        let mut shape = TopoDS_Shape_ctor();
+        if BRepTools_Read(shape.pin_mut(), path) {
+            Ok(shape.as_ref().ok_or("brep file is empty")?.into())
+        } else {
+            Err(format!("could not read brep from {path}").into())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -135,4 +174,15 @@ mod tests {
         );
         dbg!(shape.points().unwrap());
     }
+
+    #[test]
+    fn it_can_round_trip_brep() {
+        let path = std::env::temp_dir().join("dslcad_occt_round_trip.brep");
+
+        let cube = Shape::cube(1., 2., 3.).unwrap();
+        cube.write_brep(&path).unwrap();
+        let read = Shape::read_brep(&path).unwrap();
+
+        assert!((read.volume() - cube.volume()).abs() < 0.0001);
+    }
 }
@@ -1,5 +1,6 @@
 use crate::command::{Builder, Command};
 use crate::edge::Edge;
+use crate::shapes::detached;
 use crate::{DsShape, Error, Point};
 use cxx::UniquePtr;
 use opencascade_sys::ffi::{
@@ -7,7 +8,7 @@ use opencascade_sys::ffi::{
     BRepOffsetAPI_MakeOffset, BRepOffsetAPI_MakeOffset_wire_ctor, BRep_Tool_Curve,
     GProp_GProps_CentreOfMass, GProp_GProps_ctor, GeomAbs_JoinType, HandleGeomCurve,
     HandleGeomCurve_Value, TopAbs_ShapeEnum, TopExp_Explorer_ctor, TopoDS_Edge, TopoDS_Shape,
-    TopoDS_Shape_to_owned, TopoDS_Wire, TopoDS_cast_to_edge, TopoDS_cast_to_wire,
+    TopoDS_Shape_to_owned, TopoDS_cast_to_edge, TopoDS_cast_to_wire,
 };
 use std::pin::Pin;
 
@@ -26,8 +27,12 @@ impl WireFactory {
         self.make_wire.pin_mut().add_edge(&edge.0)
     }
 
-    pub fn add_wire(&mut self, wire: &Wire) {
-        self.make_wire.pin_mut().add_wire(wire.wire())
+    pub fn add_wire(&mut self, wire: &Wire) -> Result<(), Error> {
+        let wire = detached(wire.shape())?;
+        self.make_wire
+            .pin_mut()
+            .add_wire(TopoDS_cast_to_wire(&wire));
+        Ok(())
     }
 
     pub fn build(mut self) -> Result<Wire, Error> {
@@ -43,8 +48,34 @@ impl Default for WireFactory {
     }
 }
 
+/// How corners are filled in when offsetting a wire.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum JoinType {
+    #[default]
+    Arc,
+    Tangent,
+    Intersection,
+}
+
+impl From<JoinType> for GeomAbs_JoinType {
+    fn from(value: JoinType) -> Self {
+        match value {
+            JoinType::Arc => GeomAbs_JoinType::GeomAbs_Arc,
+            JoinType::Tangent => GeomAbs_JoinType::GeomAbs_Tangent,
+            JoinType::Intersection => GeomAbs_JoinType::GeomAbs_Intersection,
+        }
+    }
+}
+
+/// Distance under which points of a wire count as the same point or as lying on a line.
+const TOLERANCE: f64 = 1e-6;
+
 pub struct Wire(pub(crate) UniquePtr<TopoDS_Shape>);
 
+// SAFETY: like `Shape`, wires are never written once built.
+unsafe impl Send for Wire {}
+unsafe impl Sync for Wire {}
+
 impl DsShape for Wire {
     fn shape(&self) -> &TopoDS_Shape {
         &self.0
@@ -52,10 +83,6 @@ impl DsShape for Wire {
 }
 
 impl Wire {
-    pub(crate) fn wire(&self) -> &TopoDS_Wire {
-        TopoDS_cast_to_wire(&self.0)
-    }
-
     pub fn from_edge(left: &Edge) -> Result<Self, Error> {
         let mut wire_builder = BRepBuilderAPI_MakeWire_ctor();
         wire_builder.pin_mut().add_edge(&left.0);
@@ -65,8 +92,9 @@ impl Wire {
     }
 
     pub fn add_edge(&self, left: &Edge) -> Result<Self, Error> {
+        let wire = detached(self.shape())?;
         let mut wire_builder = BRepBuilderAPI_MakeWire_ctor();
-        wire_builder.pin_mut().add_wire(self.wire());
+        wire_builder.pin_mut().add_wire(TopoDS_cast_to_wire(&wire));
         wire_builder.pin_mut().add_edge(&left.0);
         Ok(Wire(TopoDS_Shape_to_owned(Builder::try_build(
             &mut wire_builder,
@@ -74,9 +102,10 @@ impl Wire {
     }
 
     pub fn join(&mut self, wire: &Wire) -> Result<Self, Error> {
+        let (left, right) = (detached(self.shape())?, detached(wire.shape())?);
         let mut wire_builder = BRepBuilderAPI_MakeWire_ctor();
-        wire_builder.pin_mut().add_wire(self.wire());
-        wire_builder.pin_mut().add_wire(wire.wire());
+        wire_builder.pin_mut().add_wire(TopoDS_cast_to_wire(&left));
+        wire_builder.pin_mut().add_wire(TopoDS_cast_to_wire(&right));
         Ok(Wire(TopoDS_Shape_to_owned(Builder::try_build(
             &mut wire_builder,
         )?)))
@@ -104,13 +133,102 @@ impl Wire {
         Ok(last_end)
     }
 
-    pub fn offset(&self, distance: f64) -> Result<Self, Error> {
+    pub fn is_closed(&self) -> Result<bool, Error> {
+        Ok(match (self.start()?, self.end()?) {
+            (Some(start), Some(end)) => start.distance(&end) < TOLERANCE,
+            _ => false,
+        })
+    }
+
+    /// Offsets a closed wire, open wires are thickened into a closed outline around the path. A
+    /// straight open wire gets rounded ends with [`JoinType::Arc`] and square ends otherwise.
+    pub fn offset(&self, distance: f64, join: JoinType) -> Result<Self, Error> {
+        if !self.is_closed()? {
+            if let Some((start, end)) = self.straight_ends()? {
+                return Self::outline_of_segment(&start, &end, distance.abs(), join);
+            }
+        }
+
+        // open wires are offset on both sides with rounded ends, closing the outline
+        let wire = detached(self.shape())?;
         let mut offset =
-            BRepOffsetAPI_MakeOffset_wire_ctor(self.wire(), GeomAbs_JoinType::GeomAbs_Arc);
+            BRepOffsetAPI_MakeOffset_wire_ctor(TopoDS_cast_to_wire(&wire), join.into());
         offset.pin_mut().Perform(distance, 0.0);
         Ok(Builder::try_build(&mut offset)?.into())
     }
 
+    /// The ends of the wire when all of it lies on the line between them.
+    fn straight_ends(&self) -> Result<Option<(Point, Point)>, Error> {
+        let (Some(start), Some(end)) = (self.start()?, self.end()?) else {
+            return Ok(None);
+        };
+        let length = start.distance(&end);
+        if length < TOLERANCE {
+            return Ok(None);
+        }
+
+        let direction = (end.clone() - start.clone()) / length;
+        let straight = self.points(TOLERANCE)?.into_iter().flatten().all(|p| {
+            let p = Point::new(p[0], p[1], p[2]) - start.clone();
+            let along = p.dot(&direction);
+            (p.length().powi(2) - along.powi(2)).max(0.).sqrt() < TOLERANCE
+        });
+        Ok(straight.then_some((start, end)))
+    }
+
+    /// Outline of a straight segment widened by `distance` on both sides. A single line does not
+    /// define the plane the offset algorithm needs, so it is built here in the XY plane.
+    fn outline_of_segment(
+        start: &Point,
+        end: &Point,
+        distance: f64,
+        join: JoinType,
+    ) -> Result<Self, Error> {
+        if (end.z() - start.z()).abs() > TOLERANCE {
+            return Err("only straight edges parallel to the XY plane can be thickened".into());
+        }
+        let direction = (end.clone() - start.clone()).normalized();
+        let normal = Point::new(-direction.y(), direction.x(), 0.).normalized();
+        let at = |point: &Point, offset: &Point, scale: f64| {
+            Point::new(
+                point.x() + offset.x() * scale,
+                point.y() + offset.y() * scale,
+                point.z() + offset.z() * scale,
+            )
+        };
+
+        let (start_left, start_right) =
+            (at(start, &normal, distance), at(start, &normal, -distance));
+        let (end_left, end_right) = (at(end, &normal, distance), at(end, &normal, -distance));
+
+        let mut outline = WireFactory::new();
+        if join == JoinType::Arc {
+            outline.add_edge(&Edge::new_line(&start_left, &end_left)?);
+            outline.add_edge(&Edge::new_arc(
+                &end_left,
+                &at(end, &direction, distance),
+                &end_right,
+            )?);
+            outline.add_edge(&Edge::new_line(&end_right, &start_right)?);
+            outline.add_edge(&Edge::new_arc(
+                &start_right,
+                &at(start, &direction, -distance),
+                &start_left,
+            )?);
+        } else {
+            let corners = [
+                at(&start_left, &direction, -distance),
+                at(&end_left, &direction, distance),
+                at(&end_right, &direction, distance),
+                at(&start_right, &direction, -distance),
+            ];
+            for (i, corner) in corners.iter().enumerate() {
+                outline.add_edge(&Edge::new_line(corner, &corners[(i + 1) % corners.len()])?);
+            }
+        }
+        outline.build()
+    }
+
     pub fn points(&self, deflection: f64) -> Result<Vec<Vec<[f64; 3]>>, Error> {
         let mut lines = Vec::new();
 
@@ -239,4 +357,54 @@ mod tests {
 
         assert!(!wire.points(0.1).unwrap().is_empty());
     }
+
+    #[test]
+    fn it_can_thicken_open_wires() {
+        let mut wire = WireFactory::new();
+        wire.add_edge(&Edge::new_line(&Point::new(0., 0., 0.), &Point::new(0., 10., 0.)).unwrap());
+        let wire = wire.build().unwrap();
+
+        let outline = wire.offset(1., JoinType::Arc).unwrap();
+        assert!(!wire.is_closed().unwrap());
+        assert!(outline.is_closed().unwrap());
+        let drift = outline.center_of_mass().distance(&Point::new(0., 5., 0.));
+        assert!(drift < 1e-6);
+    }
+
+    #[test]
+    fn it_squares_the_ends_of_thickened_edges() {
+        let mut wire = WireFactory::new();
+        wire.add_edge(&Edge::new_line(&Point::new(0., 0., 0.), &Point::new(0., 10., 0.)).unwrap());
+        let wire = wire.build().unwrap();
+
+        let outline = wire.offset(1., JoinType::Intersection).unwrap();
+        let ends = outline.points(0.1).unwrap().concat();
+        assert!(ends.iter().any(|p| p[1] < -1. + 1e-6));
+        assert!(ends.iter().all(|p| p[0].abs() < 1. + 1e-6));
+    }
+
+    #[test]
+    fn it_rejects_thickening_sloped_edges() {
+        let mut wire = WireFactory::new();
+        wire.add_edge(&Edge::new_line(&Point::new(0., 0., 0.), &Point::new(0., 10., 5.)).unwrap());
+        let wire = wire.build().unwrap();
+
+        assert!(wire.offset(1., JoinType::Arc).is_err());
+    }
+
+    #[test]
+    fn it_can_thicken_open_corners() {
+        let mut wire = WireFactory::new();
+        wire.add_edge(&Edge::new_line(&Point::new(0., 0., 0.), &Point::new(0., 10., 0.)).unwrap());
+        wire.add_edge(
+            &Edge::new_line(&Point::new(0., 10., 0.), &Point::new(10., 10., 0.)).unwrap(),
+        );
+        let wire = wire.build().unwrap();
+
+        assert!(wire
+            .offset(1., JoinType::Intersection)
+            .unwrap()
+            .is_closed()
+            .unwrap());
+    }
 }
@@ -1,9 +1,11 @@
 mod aabb;
+mod snapshot;
 
 use crate::threemf::{ThreeMF, Triangle, Vertex};
 pub use aabb::BoundingBox;
 pub use bincode::Error as BincodeError;
 use serde::{Deserialize, Serialize};
+pub use snapshot::{PartKind, PartSnapshot, Snapshot};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Render {
@@ -77,6 +79,12 @@ pub enum Part {
         lines: Vec<Vec<Point>>,
         mesh: Mesh,
     },
+    // new variants go last so renders written by older versions still decode
+    Face {
+        points: Vec<Point>,
+        lines: Vec<Vec<Point>>,
+        mesh: Mesh,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -99,6 +107,15 @@ mod tests {
                     points: vec![],
                     lines: vec![],
                 },
+                Part::Face {
+                    points: vec![],
+                    lines: vec![],
+                    mesh: Mesh {
+                        vertices: vec![],
+                        triangles: vec![],
+                        normals: vec![],
+                    },
+                },
                 Part::Object {
                     points: vec![Point::from((0.0, 1.0, 2.0))],
                     lines: vec![],
@@ -117,4 +134,20 @@ mod tests {
 
         assert_eq!(render, deserialized);
     }
+
+    #[test]
+    fn it_keeps_existing_variant_tags() {
+        let object = Part::Object {
+            points: vec![],
+            lines: vec![],
+            mesh: Mesh {
+                vertices: vec![],
+                triangles: vec![],
+                normals: vec![],
+            },
+        };
+
+        let serialized = bincode::serialize(&object).unwrap();
+        assert_eq!(&serialized[..4], &2u32.to_le_bytes());
+    }
 }
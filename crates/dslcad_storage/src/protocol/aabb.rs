@@ -71,6 +71,7 @@ impl Render {
                     aabb.update_from_points(points.iter());
                     aabb.update_from_points(lines.iter().flatten());
                 }
+                Part::Face { mesh, .. } => aabb.update_from_points(mesh.vertices.iter()),
                 Part::Object { mesh, .. } => aabb.update_from_points(mesh.vertices.iter()),
             }
         }
@@ -0,0 +1,265 @@
+use crate::protocol::{Mesh, Part, Point, Render};
+use serde::{Deserialize, Serialize};
+
+/// Vertices are rounded to this many decimal places before hashing so float noise does not
+/// change the hash.
+const HASH_PRECISION: f64 = 1000.;
+
+/// Measurements of the geometry in a render, used to notice when it changes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub parts: Vec<PartSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PartSnapshot {
+    pub kind: PartKind,
+    pub volume: f64,
+    pub area: f64,
+    pub min: Point,
+    pub max: Point,
+    pub triangles: usize,
+    pub mesh_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PartKind {
+    Empty,
+    Planar,
+    Face,
+    Object,
+}
+
+impl Render {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            parts: self.parts.iter().map(PartSnapshot::from).collect(),
+        }
+    }
+}
+
+impl From<&Part> for PartSnapshot {
+    fn from(part: &Part) -> Self {
+        match part {
+            Part::Empty => PartSnapshot::new(PartKind::Empty, &[], None),
+            Part::Planar { points, lines } => {
+                let points: Vec<_> = points.iter().chain(lines.iter().flatten()).collect();
+                PartSnapshot::new(PartKind::Planar, &points, None)
+            }
+            Part::Face { mesh, .. } => {
+                let points: Vec<_> = mesh.vertices.iter().collect();
+                PartSnapshot::new(PartKind::Face, &points, Some(mesh))
+            }
+            Part::Object { mesh, .. } => {
+                let points: Vec<_> = mesh.vertices.iter().collect();
+                PartSnapshot::new(PartKind::Object, &points, Some(mesh))
+            }
+        }
+    }
+}
+
+impl PartSnapshot {
+    fn new(kind: PartKind, points: &[&Point], mesh: Option<&Mesh>) -> Self {
+        let mut min = [0.; 3];
+        let mut max = [0.; 3];
+        if let Some(first) = points.first() {
+            min = **first;
+            max = **first;
+        }
+        for point in points {
+            for ((low, high), value) in min.iter_mut().zip(max.iter_mut()).zip(point.iter()) {
+                *low = low.min(*value);
+                *high = high.max(*value);
+            }
+        }
+
+        let (mut volume, mut area) = (0., 0.);
+        for [a, b, c] in mesh.iter().flat_map(|mesh| triangles(mesh)) {
+            area += length(cross(sub(b, a), sub(c, a))) / 2.;
+            volume += dot(a, cross(b, c)) / 6.;
+        }
+        if kind != PartKind::Object {
+            volume = 0.;
+        }
+
+        PartSnapshot {
+            kind,
+            volume: volume.abs(),
+            area,
+            min,
+            max,
+            triangles: mesh.map(|mesh| mesh.triangles.len()).unwrap_or_default(),
+            mesh_hash: format!("{:016x}", mesh.map(hash_mesh).unwrap_or_default()),
+        }
+    }
+}
+
+impl Snapshot {
+    /// Every value that differs from `expected` by more than `tolerance`, relative to the size of
+    /// the expected value. An empty list means the snapshots match. Triangle counts and mesh hashes
+    /// are left out, the triangulation changes with the OCCT version even when the geometry does
+    /// not.
+    pub fn differences(&self, expected: &Snapshot, tolerance: f64) -> Vec<String> {
+        if self.parts.len() != expected.parts.len() {
+            return vec![format!(
+                "expected {} parts but found {}",
+                expected.parts.len(),
+                self.parts.len()
+            )];
+        }
+
+        let mut differences = Vec::new();
+        for (i, (actual, expected)) in self.parts.iter().zip(&expected.parts).enumerate() {
+            let mut numbers = vec![
+                ("volume", actual.volume, expected.volume),
+                ("area", actual.area, expected.area),
+            ];
+            numbers.extend(
+                actual
+                    .min
+                    .iter()
+                    .zip(expected.min)
+                    .map(|(a, e)| ("min", *a, e)),
+            );
+            numbers.extend(
+                actual
+                    .max
+                    .iter()
+                    .zip(expected.max)
+                    .map(|(a, e)| ("max", *a, e)),
+            );
+
+            if actual.kind != expected.kind {
+                differences.push(format!(
+                    "part {i}: expected {:?} but found {:?}",
+                    expected.kind, actual.kind
+                ));
+            }
+            for (name, actual, expected) in numbers {
+                if (actual - expected).abs() > tolerance * expected.abs().max(1.) {
+                    differences.push(format!("part {i}: {name} was {expected} but is {actual}"));
+                }
+            }
+        }
+        differences
+    }
+}
+
+fn triangles(mesh: &Mesh) -> impl Iterator<Item = [Point; 3]> + '_ {
+    mesh.triangles.iter().map(|t| {
+        [
+            mesh.vertices[t[0]],
+            mesh.vertices[t[1]],
+            mesh.vertices[t[2]],
+        ]
+    })
+}
+
+fn sub(a: Point, b: Point) -> Point {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: Point, b: Point) -> Point {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: Point, b: Point) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn length(a: Point) -> f64 {
+    dot(a, a).sqrt()
+}
+
+/// FNV-1a hash of the mesh, stable across platforms and compiler versions unlike `Hash`.
+fn hash_mesh(mesh: &Mesh) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut write = |bytes: [u8; 8]| {
+        for byte in bytes {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+
+    for vertex in &mesh.vertices {
+        for value in vertex {
+            write(((value * HASH_PRECISION).round() as i64).to_le_bytes());
+        }
+    }
+    for triangle in &mesh.triangles {
+        for index in triangle {
+            write((*index as u64).to_le_bytes());
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tetrahedron(size: f64) -> Render {
+        let mesh = Mesh {
+            vertices: vec![[0., 0., 0.], [size, 0., 0.], [0., size, 0.], [0., 0., size]],
+            triangles: vec![[0, 2, 1], [0, 1, 3], [0, 3, 2], [1, 2, 3]],
+            normals: vec![],
+        };
+        Render {
+            parts: vec![Part::Object {
+                points: vec![],
+                lines: vec![],
+                mesh,
+            }],
+            stdout: String::new(),
+        }
+    }
+
+    #[test]
+    fn it_measures_parts() {
+        let snapshot = tetrahedron(1.).snapshot();
+        let part = &snapshot.parts[0];
+
+        assert!((part.volume - 1. / 6.).abs() < 0.0001);
+        assert!((part.area - (1.5 + 3f64.sqrt() / 2.)).abs() < 0.0001);
+        assert_eq!([1., 1., 1.], part.max);
+        assert_eq!(4, part.triangles);
+    }
+
+    #[test]
+    fn it_compares_within_tolerance() {
+        let expected = tetrahedron(1.).snapshot();
+
+        assert!(tetrahedron(1.)
+            .snapshot()
+            .differences(&expected, 0.01)
+            .is_empty());
+        assert!(!tetrahedron(1.1)
+            .snapshot()
+            .differences(&expected, 0.01)
+            .is_empty());
+
+        let moved = tetrahedron(1.001).snapshot();
+        assert_ne!(expected.parts[0].mesh_hash, moved.parts[0].mesh_hash);
+        assert!(moved.differences(&expected, 0.01).is_empty());
+    }
+
+    #[test]
+    fn it_ignores_triangle_counts() {
+        let expected = tetrahedron(1.).snapshot();
+        let mut remeshed = tetrahedron(1.);
+        if let Part::Object { mesh, .. } = &mut remeshed.parts[0] {
+            mesh.vertices.push([0.25, 0.25, 0.]);
+            mesh.triangles
+                .splice(0..1, [[0, 2, 4], [2, 1, 4], [1, 0, 4]]);
+        }
+
+        let snapshot = remeshed.snapshot();
+        assert_eq!(6, snapshot.parts[0].triangles);
+        assert!(snapshot.differences(&expected, 0.01).is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 use quick_xml::{se, DeError};
 use serde::{Deserialize, Serialize};
 use std::io::{Seek, Write};
+use std::str::FromStr;
 use thiserror::Error;
 use zip::result::ZipError;
 use zip::write::{FileOptions, ZipWriter};
@@ -56,6 +57,10 @@ impl ThreeMF {
         self.model.add_object(vertices, triangles);
     }
 
+    pub fn set_unit(&mut self, unit: Unit) {
+        self.model.unit = unit;
+    }
+
     pub fn write_to_zip(&self, writer: impl Write + Seek) -> Result<(), ThreeMFError> {
         let mut zip = ZipWriter::new(writer);
         let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
@@ -157,11 +162,52 @@ impl Model {
     }
 }
 
-#[derive(Serialize, Deserialize, Default)]
-enum Unit {
-    #[serde(rename = "millimeter")]
+/// Units supported by 3MF models.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    Micron,
     #[default]
     Millimeter,
+    Centimeter,
+    Inch,
+    Foot,
+    Meter,
+}
+
+#[derive(Debug, Error)]
+#[error("unknown unit {0}, expected one of micron, millimeter, centimeter, inch, foot or meter")]
+pub struct UnknownUnit(String);
+
+impl Unit {
+    /// Length of one of this unit in millimeters.
+    pub fn millimeters(self) -> f64 {
+        match self {
+            Unit::Micron => 0.001,
+            Unit::Millimeter => 1.,
+            Unit::Centimeter => 10.,
+            Unit::Inch => 25.4,
+            Unit::Foot => 304.8,
+            Unit::Meter => 1000.,
+        }
+    }
+}
+
+impl FromStr for Unit {
+    type Err = UnknownUnit;
+
+    /// Parse the full name or the abbreviation of a unit.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "micron" | "um" => Ok(Unit::Micron),
+            "millimeter" | "mm" => Ok(Unit::Millimeter),
+            "centimeter" | "cm" => Ok(Unit::Centimeter),
+            "inch" | "in" => Ok(Unit::Inch),
+            "foot" | "ft" => Ok(Unit::Foot),
+            "meter" | "m" => Ok(Unit::Meter),
+            _ => Err(UnknownUnit(s.to_string())),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -318,4 +364,16 @@ mod tests {
         assert!(buf.contains("<vertices>"));
         assert!(buf.contains("<triangles>"));
     }
+
+    #[test]
+    fn it_writes_units() {
+        let mut file = ThreeMF::default();
+        file.set_unit(Unit::from_str("in").unwrap());
+
+        let mut buf = String::new();
+        ThreeMF::write_model(&mut buf, &file.model).unwrap();
+
+        assert!(buf.contains("unit=\"inch\""));
+        assert!(Unit::from_str("furlong").is_err());
+    }
 }
@@ -9,11 +9,12 @@ use bevy_polyline::prelude::*;
 use std::error::Error;
 
 use crate::editor::camera::CameraCommand;
+use crate::editor::gui::ParameterPanel;
 use crate::editor::rendering::RenderCommand;
 use crate::settings::Settings;
-use crate::PreviewEvent;
+use crate::{HostMessage, PreviewEvent};
 use bevy::log::LogPlugin;
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{Receiver, Sender};
 use std::sync::Mutex;
 
 struct Blueprint;
@@ -38,6 +39,7 @@ impl Blueprint {
 pub(crate) fn main(
     cheatsheet: String,
     rx: Receiver<PreviewEvent>,
+    host: Sender<HostMessage>,
     store: Settings,
 ) -> Result<(), Box<dyn Error>> {
     let mut app = App::new();
@@ -64,13 +66,14 @@ pub(crate) fn main(
         .add_plugins((
             PolylinePlugin,
             camera::CameraPlugin,
-            gui::GuiPlugin::new(cheatsheet),
+            gui::GuiPlugin::new(cheatsheet, host),
             xyz::XYZPlugin,
             rendering::ModelRenderingPlugin,
         ))
         .add_systems(
             Update,
             move |mut console: ResMut<gui::Console>,
+                  mut parameters: ResMut<ParameterPanel>,
                   mut re: EventWriter<RenderCommand>,
                   mut ca: EventWriter<CameraCommand>| {
                 let rx = rx.lock().unwrap();
@@ -90,6 +93,9 @@ pub(crate) fn main(
                     Ok(PreviewEvent::Error(e)) => {
                         console.print(e);
                     }
+                    Ok(PreviewEvent::Parameters(p)) => {
+                        parameters.show(p);
+                    }
                     _ => {}
                 }
             },
@@ -1,5 +1,6 @@
 mod help;
 mod menu;
+mod parameters;
 mod view_menu;
 
 use bevy::prelude::*;
@@ -10,15 +11,21 @@ use bevy_egui::{egui, EguiContext, EguiPlugin};
 
 use crate::editor::gui::help::HelpPlugin;
 use crate::editor::gui::menu::{MenuAppExt, MenuPlugin};
+use crate::editor::gui::parameters::ParametersPlugin;
 use crate::editor::gui::view_menu::ViewMenuPlugin;
+use crate::HostMessage;
+use std::sync::mpsc::Sender;
+
+pub use crate::editor::gui::parameters::ParameterPanel;
 
 pub struct GuiPlugin {
     cheetsheet: String,
+    host: Sender<HostMessage>,
 }
 
 impl GuiPlugin {
-    pub fn new(cheetsheet: String) -> Self {
-        Self { cheetsheet }
+    pub fn new(cheetsheet: String, host: Sender<HostMessage>) -> Self {
+        Self { cheetsheet, host }
     }
 }
 
@@ -38,6 +45,7 @@ impl Plugin for GuiPlugin {
             c.send(CameraCommand::Reset());
         })
         .add_plugins(HelpPlugin::default())
+        .add_plugins(ParametersPlugin::new(self.host.clone()))
         .add_systems(Update, console_panel);
     }
 }
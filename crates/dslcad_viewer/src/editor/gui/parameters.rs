@@ -0,0 +1,212 @@
+use crate::{HostMessage, Parameter};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{egui, EguiContext};
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::sync::mpsc::Sender;
+
+pub struct ParametersPlugin {
+    host: Sender<HostMessage>,
+}
+
+impl ParametersPlugin {
+    pub fn new(host: Sender<HostMessage>) -> Self {
+        Self { host }
+    }
+}
+
+impl Plugin for ParametersPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Host(self.host.clone()))
+            .init_resource::<ParameterPanel>()
+            .add_systems(Update, parameters_panel);
+    }
+}
+
+#[derive(Resource)]
+struct Host(Sender<HostMessage>);
+
+/// Inputs for the parameters of the previewed script.
+#[derive(Resource, Default)]
+pub struct ParameterPanel {
+    fields: Vec<Field>,
+}
+
+impl ParameterPanel {
+    /// Show the parameters of a new render. Fields are matched by name, so inputs that were
+    /// edited or are still being typed in keep their value across re-renders.
+    pub fn show(&mut self, parameters: Vec<Parameter>) {
+        let mut previous: HashMap<_, _> = self
+            .fields
+            .drain(..)
+            .map(|field| (field.parameter.name.clone(), field))
+            .collect();
+
+        self.fields = parameters
+            .into_iter()
+            .map(|parameter| match previous.remove(&parameter.name) {
+                Some(field) if field.edited || field.typing => Field { parameter, ..field },
+                _ => Field::new(parameter),
+            })
+            .collect();
+    }
+
+    /// Arguments for every parameter that was passed in or edited.
+    fn arguments(&self) -> Vec<String> {
+        self.fields.iter().filter_map(Field::argument).collect()
+    }
+}
+
+struct Field {
+    parameter: Parameter,
+    input: Input,
+    edited: bool,
+    /// Text was typed but not yet committed by leaving the input.
+    typing: bool,
+}
+
+enum Input {
+    Number(f64, RangeInclusive<f64>),
+    Bool(bool),
+    Text(String),
+    /// Any other literal, edited as source.
+    Literal(String),
+}
+
+impl Field {
+    fn new(parameter: Parameter) -> Self {
+        let value = parameter.value.trim();
+        let input = if let Ok(number) = value.parse::<f64>() {
+            let max = (number.abs() * 2.).max(1.);
+            let min = if number < 0. { -max } else { 0. };
+            Input::Number(number, min..=max)
+        } else if let Ok(value) = value.parse::<bool>() {
+            Input::Bool(value)
+        } else if let Some(text) = unquote(value) {
+            Input::Text(text)
+        } else {
+            Input::Literal(value.to_string())
+        };
+
+        Self {
+            edited: parameter.is_argument,
+            parameter,
+            input,
+            typing: false,
+        }
+    }
+
+    fn argument(&self) -> Option<String> {
+        if !self.edited {
+            return None;
+        }
+
+        let value = match &self.input {
+            Input::Number(value, _) => value.to_string(),
+            Input::Bool(value) => value.to_string(),
+            Input::Text(text) => quote(text),
+            Input::Literal(literal) if literal.trim().is_empty() => return None,
+            Input::Literal(literal) => literal.trim().to_string(),
+        };
+        Some(format!("{}={value}", self.parameter.name))
+    }
+
+    /// Draw the input, returning whether a new value should be rendered.
+    fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let label = ui.label(&self.parameter.name);
+        if let Some(doc) = &self.parameter.doc {
+            label.on_hover_text(doc);
+        }
+
+        let committed = match &mut self.input {
+            Input::Number(value, range) => {
+                let response = ui.add(
+                    egui::Slider::new(value, range.clone()).clamping(egui::SliderClamping::Never),
+                );
+                response.drag_stopped() || (response.changed() && !response.dragged())
+            }
+            Input::Bool(value) => ui.checkbox(value, "").changed(),
+            Input::Text(text) | Input::Literal(text) => {
+                let response = ui.text_edit_singleline(text);
+                self.typing |= response.changed();
+                let committed = self.typing && response.lost_focus();
+                if committed {
+                    self.typing = false;
+                }
+                committed
+            }
+        };
+        ui.end_row();
+
+        self.edited |= committed;
+        committed
+    }
+}
+
+/// Text of a string literal, `None` when `value` is not a single string literal.
+fn unquote(value: &str) -> Option<String> {
+    let mut chars = value.strip_prefix('"')?.chars();
+    let mut text = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return chars.as_str().is_empty().then_some(text),
+            '\\' => match chars.next()? {
+                'r' => text.push('\r'),
+                'n' => text.push('\n'),
+                't' => text.push('\t'),
+                c @ ('"' | '\\') => text.push(c),
+                c => text.extend(['\\', c]),
+            },
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+/// String literal for `text`, the inverse of `unquote`.
+fn quote(text: &str) -> String {
+    let mut literal = String::from('"');
+    for c in text.chars() {
+        match c {
+            '\r' => literal.push_str(r"\r"),
+            '\n' => literal.push_str(r"\n"),
+            '\t' => literal.push_str(r"\t"),
+            '"' | '\\' => literal.extend(['\\', c]),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+fn parameters_panel(
+    mut panel: ResMut<ParameterPanel>,
+    host: Res<Host>,
+    mut egui_ctx: Query<&mut EguiContext, With<PrimaryWindow>>,
+) {
+    if panel.fields.is_empty() {
+        return;
+    }
+
+    let mut committed = false;
+    egui::SidePanel::right("Parameters").show(egui_ctx.single_mut().get_mut(), |ui| {
+        ui.heading("Parameters:");
+        ui.separator();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("Parameter Inputs")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for field in panel.fields.iter_mut() {
+                        committed |= field.ui(ui);
+                    }
+                });
+        });
+    });
+
+    if committed {
+        // the host stops listening when it exits, there is nothing left to render then
+        let _ = host.0.send(HostMessage::Arguments(panel.arguments()));
+    }
+}
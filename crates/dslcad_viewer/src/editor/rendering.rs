@@ -82,7 +82,7 @@ fn mesh_renderer(
             };
 
             for (i, part) in parts.iter().enumerate() {
-                if let Part::Object { mesh, .. } = part {
+                if let Part::Object { mesh, .. } | Part::Face { mesh, .. } = part {
                     let mesh = stl_to_triangle_mesh(mesh);
 
                     let color = if render_state.part_colors {
@@ -136,6 +136,13 @@ fn point_renderer(
                         points,
                         *entity,
                     ),
+                    Part::Face { points, .. } => render_points(
+                        &mut commands,
+                        &mut meshes,
+                        &mut point_materials,
+                        points,
+                        *entity,
+                    ),
                     Part::Object { points, .. } => render_points(
                         &mut commands,
                         &mut meshes,
@@ -208,6 +215,13 @@ fn line_renderer(
                         lines,
                         *entity,
                     ),
+                    Part::Face { lines, .. } => render_lines(
+                        &mut commands,
+                        &mut polylines,
+                        &mut polyline_materials,
+                        lines,
+                        *entity,
+                    ),
                     Part::Object { lines, .. } => render_lines(
                         &mut commands,
                         &mut polylines,
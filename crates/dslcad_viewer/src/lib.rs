@@ -4,16 +4,37 @@ mod settings;
 use crate::settings::Settings;
 use dslcad_storage::protocol::Render;
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
 
 enum PreviewEvent {
     Rendering,
     Render(Render),
     Error(String),
+    Parameters(Vec<Parameter>),
+}
+
+/// Sent from the viewer to the program that opened it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostMessage {
+    /// Arguments as `name=value` to render the script with, replacing the previous ones.
+    Arguments(Vec<String>),
+}
+
+/// A parameter of the previewed script, editable in the parameters panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    /// Literal or expression the parameter is set to, empty when it has no value yet.
+    pub value: String,
+    /// Whether `value` was passed as an argument rather than being the script's default.
+    pub is_argument: bool,
+    pub doc: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct PreviewHandle {
     tx: Sender<PreviewEvent>,
+    messages: Arc<Mutex<Receiver<HostMessage>>>,
 }
 
 impl PreviewHandle {
@@ -28,19 +49,40 @@ impl PreviewHandle {
     pub fn show_error(&self, error: String) {
         self.tx.send(PreviewEvent::Error(error)).unwrap()
     }
+
+    pub fn show_parameters(&self, parameters: Vec<Parameter>) {
+        self.tx.send(PreviewEvent::Parameters(parameters)).unwrap()
+    }
+
+    /// Wait for the next message from the viewer, skipping to the newest when several are
+    /// queued. Returns `None` once the viewer is closed.
+    pub fn receive(&self) -> Option<HostMessage> {
+        let messages = self.messages.lock().unwrap();
+        let mut message = messages.recv().ok()?;
+        while let Ok(newer) = messages.try_recv() {
+            message = newer;
+        }
+        Some(message)
+    }
 }
 
 pub struct Preview {
     rx: Receiver<PreviewEvent>,
+    host: Sender<HostMessage>,
 }
 
 impl Preview {
     pub fn new() -> (Self, PreviewHandle) {
         let (tx, rx) = channel();
-        (Self { rx }, PreviewHandle { tx })
+        let (host, messages) = channel();
+        let handle = PreviewHandle {
+            tx,
+            messages: Arc::new(Mutex::new(messages)),
+        };
+        (Self { rx, host }, handle)
     }
 
     pub fn open(self, cheetsheet: String) {
-        editor::main(cheetsheet, self.rx, Settings::default()).unwrap();
+        editor::main(cheetsheet, self.rx, self.host, Settings::default()).unwrap();
     }
 }